@@ -3,12 +3,261 @@
 //! author: https://github.com/vincenzopalazzo
 use std::io;
 
+use monkey_common::src_ir::SrcNode;
+
+use super::tags::GoOptions;
+use super::visitor::GoVisitor;
+use crate::schema::lower_model;
 use crate::{CodeGenStrategy, DataModel};
 
-pub struct Golang;
+pub struct Golang {
+    /// Name of the Go package of the generated source
+    package: String,
+    options: GoOptions,
+}
+
+impl Golang {
+    pub fn new(package: &str) -> Self {
+        Golang::new_with_options(package, GoOptions::default())
+    }
+
+    pub fn new_with_options(package: &str, options: GoOptions) -> Self {
+        Golang {
+            package: package.to_string(),
+            options,
+        }
+    }
+}
 
 impl<'a> CodeGenStrategy<'a> for Golang {
-    fn generate(&self, model: &'a DataModel) -> Result<(), io::Error> {
-        Ok(())
+    fn extension(&self) -> &str {
+        "go"
+    }
+
+    fn generate(&self, model: &'a DataModel, out: &mut dyn io::Write) -> Result<(), io::Error> {
+        let objs = lower_model(model)?;
+        let mut visitor = GoVisitor::new(&self.options);
+        for obj in objs {
+            visitor.source.push('\n');
+            obj.accept(&mut visitor);
+        }
+        writeln!(out, "// Code generated by monkyc. DO NOT EDIT.")?;
+        writeln!(out)?;
+        writeln!(out, "package {}", self.package)?;
+        write!(out, "{}", visitor.source)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use monkey_yaml::ParseOptions;
+
+    use super::Golang;
+    use crate::golang::tags::{GoOptions, TagFamily, TagNaming};
+    use crate::schema::SchemaError;
+    use crate::{CodeGenStrategy, DataModel};
+
+    fn generate(content: &str) -> String {
+        generate_with_options(content, GoOptions::default())
+    }
+
+    fn generate_with_options(content: &str, options: GoOptions) -> String {
+        let ir = monkey_yaml::parse_str(content, ParseOptions::default()).unwrap();
+        let mut out = vec![];
+        Golang::new_with_options("model", options)
+            .generate(&DataModel::Yaml(&ir), &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn generate_model_one() {
+        let source = generate(include_str!("../../../examples/model_one.yml"));
+        assert_eq!(source, include_str!("../../tests/golden/model_one.go"));
+    }
+
+    #[test]
+    fn generate_model_one_from_json() {
+        let ir = monkey_json::parse_str(include_str!("../../../examples/model_one.json")).unwrap();
+        let mut out = vec![];
+        Golang::new("model")
+            .generate(&DataModel::Json(&ir), &mut out)
+            .unwrap();
+        let source = String::from_utf8(out).unwrap();
+        assert_eq!(
+            source,
+            generate(include_str!("../../../examples/model_one.yml"))
+        );
+    }
+
+    #[test]
+    fn generate_collections_and_visibility() {
+        let model = indoc! {"
+ModelTwo:
+  - user_name:
+      - doc_comment: \"Name of the user\"
+      - type: \"string\"
+      - visibility: \"public\"
+      - nullable: false
+  - user_score:
+      - type: \"float\"
+      - visibility: \"private\"
+  - tags:
+      - doc_comment: \"Tags of the user\"
+      - type: \"[]string\"
+      - visibility: \"public\"
+      - nullable: true
+  - friends:
+      - type: \"map[string][]ModelTwo\"
+      - visibility: \"public\"
+  - active:
+      - type: \"bool\"
+      - visibility: \"public\"
+      - reference: true
+"};
+        let source = generate(model);
+        assert_eq!(source, include_str!("../../tests/golden/model_two.go"));
+    }
+
+    #[test]
+    fn generate_model_one_with_tags() {
+        let options = GoOptions {
+            tags: vec![TagFamily::Json, TagFamily::Yaml],
+            naming: TagNaming::SnakeCase,
+        };
+        let source =
+            generate_with_options(include_str!("../../../examples/model_one.yml"), options);
+        assert_eq!(source, include_str!("../../tests/golden/model_one_tags.go"));
+    }
+
+    #[test]
+    fn generate_tagged_types() {
+        let model = indoc! {"
+ModelOne:
+  - state: {type: !enum [Active, Disabled]}
+  - parent: {type: !ref ModelOne, nullable: true}
+  - count: {type: int64, doc_comment: !!str 12}
+"};
+        let expected = indoc! {"
+// Code generated by monkyc. DO NOT EDIT.
+
+package model
+
+type ModelOne struct {
+	State  string    `json:\"state\"`
+	Parent *ModelOne `json:\"parent,omitempty\"`
+	// 12
+	Count  int64     `json:\"count\"`
+}
+"};
+        assert_eq!(generate(model).trim_end(), expected.trim_end());
+    }
+
+    #[test]
+    fn generate_mapping_properties() {
+        let model = indoc! {"
+ModelOne:
+  PropOne:
+    doc_comment: Documentation document
+    type: int64
+    nullable: true
+  PropTwo: {type: ModelOne, visibility: public}
+"};
+        let expected = indoc! {"
+// Code generated by monkyc. DO NOT EDIT.
+
+package model
+
+type ModelOne struct {
+	// Documentation document
+	PropOne *int64   `json:\"PropOne,omitempty\"`
+	PropTwo ModelOne `json:\"PropTwo\"`
+}
+"};
+        assert_eq!(generate(model).trim_end(), expected.trim_end());
+    }
+
+    #[test]
+    fn generate_merged_properties() {
+        let model = indoc! {"
+Audit: &audit
+  created_at: {type: int64}
+  updated_at: {type: int64}
+ModelOne:
+  - <<: *audit
+  - updated_at: {type: string}
+  - name: {type: string}
+"};
+        let expected = indoc! {"
+// Code generated by monkyc. DO NOT EDIT.
+
+package model
+
+type Audit struct {
+	CreatedAt int64 `json:\"created_at\"`
+	UpdatedAt int64 `json:\"updated_at\"`
+}
+
+type ModelOne struct {
+	CreatedAt int64  `json:\"created_at\"`
+	UpdatedAt string `json:\"updated_at\"`
+	Name      string `json:\"name\"`
+}
+"};
+        assert_eq!(generate(model).trim_end(), expected.trim_end());
+    }
+
+    #[test]
+    fn generate_comment_docs() {
+        let model = indoc! {"
+# Users of the system
+ModelOne:
+  # Name of the user
+  name: {type: string}
+  # ignored, the doc_comment key is used
+  age: {type: int, doc_comment: Age of the user}
+  # Tags of the user
+  tags: # not a doc
+    type: \"[]string\"
+"};
+        let expected = indoc! {"
+// Code generated by monkyc. DO NOT EDIT.
+
+package model
+
+// Users of the system
+type ModelOne struct {
+	// Name of the user
+	Name string   `json:\"name\"`
+	// Age of the user
+	Age  int      `json:\"age\"`
+	// Tags of the user
+	Tags []string `json:\"tags\"`
+}
+"};
+        assert_eq!(generate(model).trim_end(), expected.trim_end());
+    }
+
+    #[test]
+    fn generate_unknown_type_error() {
+        let model = indoc! {"
+ModelOne:
+  - PropOne:
+      - type: \"ModelTwo\"
+"};
+        let ir = monkey_yaml::parse_str(model, ParseOptions::default()).unwrap();
+        let err = Golang::new("model")
+            .generate(&DataModel::Yaml(&ir), &mut vec![])
+            .unwrap_err();
+        let err = err
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<SchemaError>()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "model `ModelOne`, property `PropOne`: unknown type `ModelTwo` at line 3, column 15"
+        );
     }
 }
@@ -3,3 +3,5 @@
 //! author: https://github.com/vincenzopalazzo
 
 pub mod codegen;
+pub mod tags;
+pub mod visitor;
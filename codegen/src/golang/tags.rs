@@ -0,0 +1,198 @@
+//! Go struct tags implementation, that give the possibility
+//! to marshal the generated struct with the Go encoders.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::str::FromStr;
+
+/// Family of struct tag that the Go generator can emit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TagFamily {
+    Json,
+    Yaml,
+}
+
+impl TagFamily {
+    /// Key of the tag family, e.g: `json:"..."`
+    fn key(&self) -> &str {
+        match self {
+            TagFamily::Json => "json",
+            TagFamily::Yaml => "yaml",
+        }
+    }
+}
+
+impl FromStr for TagFamily {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(TagFamily::Json),
+            "yaml" => Ok(TagFamily::Yaml),
+            _ => Err(format!("unknown tag family `{s}`, expected json or yaml")),
+        }
+    }
+}
+
+/// Naming convention of the key inside the struct tag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TagNaming {
+    /// Keep the key as defined in the model
+    Original,
+    /// e.g: prop_one
+    SnakeCase,
+    /// e.g: propOne
+    CamelCase,
+    /// e.g: prop-one
+    KebabCase,
+}
+
+impl TagNaming {
+    /// Convert the name given in input with the naming convention.
+    pub fn apply(&self, name: &str) -> String {
+        let words = split_words(name);
+        match self {
+            TagNaming::Original => name.to_string(),
+            TagNaming::SnakeCase => words.join("_"),
+            TagNaming::KebabCase => words.join("-"),
+            TagNaming::CamelCase => words
+                .iter()
+                .enumerate()
+                .map(|(idx, word)| {
+                    if idx == 0 {
+                        return word.to_string();
+                    }
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first.to_uppercase().chain(chars).collect(),
+                        None => String::new(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FromStr for TagNaming {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "original" => Ok(TagNaming::Original),
+            "snake" | "snake_case" => Ok(TagNaming::SnakeCase),
+            "camel" | "camelCase" => Ok(TagNaming::CamelCase),
+            "kebab" | "kebab-case" => Ok(TagNaming::KebabCase),
+            _ => Err(format!(
+                "unknown tag naming `{s}`, expected original, snake, camel or kebab"
+            )),
+        }
+    }
+}
+
+/// Options of the Go code generator.
+#[derive(Debug, Clone)]
+pub struct GoOptions {
+    /// Struct tags emitted for each field
+    pub tags: Vec<TagFamily>,
+    /// Naming convention of the key inside the struct tags
+    pub naming: TagNaming,
+}
+
+impl Default for GoOptions {
+    fn default() -> Self {
+        GoOptions {
+            tags: vec![TagFamily::Json],
+            naming: TagNaming::Original,
+        }
+    }
+}
+
+impl GoOptions {
+    /// Build the struct tag of the field, e.g:
+    /// `json:"prop_one,omitempty" yaml:"prop_one,omitempty"`
+    /// return None if there is no tag to emit.
+    pub fn struct_tag(&self, name: &str, omitempty: bool) -> Option<String> {
+        if self.tags.is_empty() {
+            return None;
+        }
+        let key = self.naming.apply(name);
+        let value = if omitempty {
+            format!("{key},omitempty")
+        } else {
+            key
+        };
+        let tags: Vec<String> = self
+            .tags
+            .iter()
+            .map(|family| format!("{}:\"{value}\"", family.key()))
+            .collect();
+        Some(format!("`{}`", tags.join(" ")))
+    }
+}
+
+/// Split a name in lower case words, the name can be in
+/// snake_case, kebab-case, camelCase or PascalCase, e.g:
+/// `HTTPServerName` -> [http, server, name].
+fn split_words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = vec![];
+    let mut word = String::new();
+    for (idx, ch) in chars.iter().enumerate() {
+        if !ch.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(word);
+                word = String::new();
+            }
+            continue;
+        }
+        if ch.is_uppercase() && !word.is_empty() {
+            let prev = chars[idx - 1];
+            let next_is_lower = chars.get(idx + 1).is_some_and(|next| next.is_lowercase());
+            // start a new word on `aB` and on the last upper case
+            // letter of an acronym like `HTTPServer`
+            if !prev.is_uppercase() || next_is_lower {
+                words.push(word);
+                word = String::new();
+            }
+        }
+        word.extend(ch.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GoOptions, TagFamily, TagNaming};
+
+    #[test]
+    fn tag_naming_conventions() {
+        assert_eq!(TagNaming::SnakeCase.apply("PropOne"), "prop_one");
+        assert_eq!(
+            TagNaming::SnakeCase.apply("HTTPServerName"),
+            "http_server_name"
+        );
+        assert_eq!(TagNaming::CamelCase.apply("prop_one"), "propOne");
+        assert_eq!(TagNaming::CamelCase.apply("PropOne"), "propOne");
+        assert_eq!(TagNaming::KebabCase.apply("propOne"), "prop-one");
+        assert_eq!(TagNaming::Original.apply("Prop_One"), "Prop_One");
+    }
+
+    #[test]
+    fn struct_tag_with_families() {
+        let options = GoOptions {
+            tags: vec![TagFamily::Json, TagFamily::Yaml],
+            naming: TagNaming::SnakeCase,
+        };
+        assert_eq!(
+            options.struct_tag("PropOne", true).unwrap(),
+            "`json:\"prop_one,omitempty\" yaml:\"prop_one,omitempty\"`"
+        );
+        let options = GoOptions {
+            tags: vec![],
+            naming: TagNaming::SnakeCase,
+        };
+        assert_eq!(options.struct_tag("PropOne", false), None);
+    }
+}
@@ -0,0 +1,208 @@
+//! Golang visitor implementation, that walk through
+//! the source IR and build the Go source code.
+//!
+//! author: https://github.com/vincenzopalazzo
+use monkey_common::src_ir::{
+    BagMetaNode, BagNode, BoolMetaNode, CharMetaNode, CharNode, ListType, MapType, MetaNode,
+    NumberMetaNode, NumberNode, ObjMetaNode, RefMetaNode, VisibilityMeta,
+};
+use monkey_common::src_visit::OutVisitor;
+
+use super::tags::GoOptions;
+
+/// Go visitor that collect the Go source code
+/// of the nodes visited.
+pub struct GoVisitor {
+    /// Go source code produced by the visitor
+    pub source: String,
+    /// Go type of the last type node visited
+    tipe: String,
+    options: GoOptions,
+}
+
+impl GoVisitor {
+    pub fn new(options: &GoOptions) -> Self {
+        GoVisitor {
+            source: String::new(),
+            tipe: String::new(),
+            options: options.clone(),
+        }
+    }
+
+    /// Write the comment as a Go comment with the indentation
+    /// given in input.
+    fn write_comment(&mut self, comment: &str, indent: &str) {
+        for line in comment.lines() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                self.source.push_str(&format!("{indent}//\n"));
+            } else {
+                self.source.push_str(&format!("{indent}// {line}\n"));
+            }
+        }
+    }
+
+    /// Set the type of the node visited, and make it a pointer
+    /// when the node is nullable or a reference.
+    fn set_type(&mut self, metadata: &MetaNode, tipe: &str) {
+        let pointer = metadata.nullable.unwrap_or(false) || metadata.pointer.unwrap_or(false);
+        self.tipe = if pointer {
+            format!("*{tipe}")
+        } else {
+            tipe.to_string()
+        };
+    }
+}
+
+impl OutVisitor for GoVisitor {
+    fn visit_obj(&mut self, obj: &ObjMetaNode) {
+        if let Some(comment) = &obj.metadata.comment {
+            self.write_comment(comment, "");
+        }
+        self.source
+            .push_str(&format!("type {} struct {{\n", go_ident(&obj.name, true)));
+
+        let mut fields = vec![];
+        for field in &obj.fields {
+            let metadata = field.node.metadata();
+            let exported = !matches!(metadata.visibility, Some(VisibilityMeta::Private));
+            let omitempty = metadata.nullable.unwrap_or(false);
+            field.node.accept(self);
+            // the Go encoders ignore the unexported fields, so
+            // a tag on them is only noise
+            let tag = match exported {
+                true => self.options.struct_tag(&field.name, omitempty),
+                false => None,
+            };
+            fields.push((
+                metadata.comment.clone(),
+                go_ident(&field.name, exported),
+                self.tipe.clone(),
+                tag,
+            ));
+        }
+
+        // align the field types and tags like gofmt does, where
+        // a field without tag ends the alignment of the tags
+        let name_width = fields
+            .iter()
+            .map(|(_, name, _, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        let mut type_width = 0;
+        for (idx, (comment, name, tipe, tag)) in fields.iter().enumerate() {
+            if let Some(comment) = comment {
+                self.write_comment(comment, "\t");
+            }
+            let line = match tag {
+                Some(tag) => {
+                    if idx == 0 || fields[idx - 1].3.is_none() {
+                        type_width = fields[idx..]
+                            .iter()
+                            .take_while(|(_, _, _, tag)| tag.is_some())
+                            .map(|(_, _, tipe, _)| tipe.len())
+                            .max()
+                            .unwrap_or(0);
+                    }
+                    format!("\t{name:<name_width$} {tipe:<type_width$} {tag}\n")
+                }
+                None => format!("\t{name:<name_width$} {tipe}\n"),
+            };
+            self.source.push_str(&line);
+        }
+        self.source.push_str("}\n");
+    }
+
+    fn visit_ref(&mut self, reference: &RefMetaNode) {
+        self.set_type(&reference.metadata, &go_ident(&reference.name, true));
+    }
+
+    fn visit_char(&mut self, string: &CharMetaNode) {
+        let tipe = match string.tipe {
+            CharNode::Char => "rune",
+            CharNode::Str => "string",
+        };
+        self.set_type(&string.metadata, tipe);
+    }
+
+    fn visit_number(&mut self, int: &NumberMetaNode) {
+        let tipe = match int.tipe {
+            NumberNode::IntNode => "int",
+            NumberNode::Int32Node => "int32",
+            NumberNode::Int64Node => "int64",
+            NumberNode::Float32Node => "float32",
+            NumberNode::Float64Node => "float64",
+        };
+        self.set_type(&int.metadata, tipe);
+    }
+
+    fn visit_bool(&mut self, boolean: &BoolMetaNode) {
+        self.set_type(&boolean.metadata, "bool");
+    }
+
+    fn visit_collection(&mut self, bag: &BagMetaNode) {
+        // slice and map are already nullable in Go, so
+        // we do not need a pointer here.
+        self.tipe = go_bag_type(&bag.tipe);
+    }
+}
+
+/// Convert a collection in the Go type.
+fn go_bag_type(bag: &BagNode) -> String {
+    match bag {
+        BagNode::ListNode(ListType {
+            type_name,
+            complex_type,
+        }) => format!("[]{}", go_elem_type(type_name, complex_type)),
+        BagNode::MapNode(MapType {
+            key_type,
+            value_type,
+            complex_value,
+        }) => format!(
+            "map[{}]{}",
+            go_type_name(key_type),
+            go_elem_type(value_type, complex_value)
+        ),
+        BagNode::Enum(_) => "string".to_string(),
+    }
+}
+
+fn go_elem_type(type_name: &Option<String>, complex_type: &Option<BagNode>) -> String {
+    match (type_name, complex_type) {
+        (_, Some(bag)) => go_bag_type(bag),
+        (Some(name), None) => go_type_name(name),
+        (None, None) => "interface{}".to_string(),
+    }
+}
+
+/// Convert a type name of the schema in the Go type name.
+fn go_type_name(name: &str) -> String {
+    match name {
+        "string" => "string".to_string(),
+        "char" => "rune".to_string(),
+        "float" | "double" => "float64".to_string(),
+        "boolean" => "bool".to_string(),
+        "int" | "int32" | "int64" | "float32" | "float64" | "bool" => name.to_string(),
+        _ => go_ident(name, true),
+    }
+}
+
+/// Convert a name of the model in a Go identifier, the
+/// identifier is exported when it starts with a upper case
+/// letter, e.g: `prop_one` -> `PropOne` or `propOne`.
+pub fn go_ident(name: &str, exported: bool) -> String {
+    let mut ident = String::new();
+    for word in name.split(['_', '-', ' ']).filter(|word| !word.is_empty()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            ident.extend(first.to_uppercase());
+            ident.push_str(chars.as_str());
+        }
+    }
+    let mut chars = ident.chars();
+    match chars.next() {
+        Some(first) if exported => first.to_uppercase().chain(chars).collect(),
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => ident,
+    }
+}
@@ -6,21 +6,29 @@
 //! author: https://github.com/vincenzopalazzo
 use std::{io, vec};
 
-use monkey_yaml::parser::tokens::YamlObject;
+use monkey_yaml::parser::tokens::YamlNode;
 
 pub mod golang;
+pub mod schema;
 
 /// Data model enum with all type of Data model
 /// supported by the code gen module.
 pub enum DataModel<'a> {
-    Yaml(&'a vec::Vec<YamlObject>),
+    Yaml(&'a vec::Vec<YamlNode>),
     JsonSchema,
-    Json,
+    /// JSON model lowered in the same tree of the YAML model,
+    /// e.g: by `monkey_json::parse_str`.
+    Json(&'a vec::Vec<YamlNode>),
 }
 
 /// Code Gene Strategy traits
 pub trait CodeGenStrategy<'a> {
+    /// File extension of the source code generated
+    /// by the strategy, e.g: `go`.
+    fn extension(&self) -> &str;
+
     /// generic method to generate source code
-    /// from a Data Model Abstraction
-    fn generate(&'a self, model: &'a DataModel) -> Result<(), io::Error>;
+    /// from a Data Model Abstraction, and write it
+    /// inside the `out` writer.
+    fn generate(&'a self, model: &'a DataModel, out: &mut dyn io::Write) -> Result<(), io::Error>;
 }
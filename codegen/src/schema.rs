@@ -0,0 +1,378 @@
+//! Model schema implementation, that lower a Data Model
+//! in the source code IR.
+//!
+//! A model is defined as a map of properties, and each property
+//! is described by the following keys:
+//!
+//! - doc_comment: documentation of the property;
+//! - type: type of the property, it can be a primitive type, a
+//!   collection like `[]int64`, `list[int64]` or `map[string]int64`,
+//!   or the name of another model;
+//! - visibility: `public` or `private`;
+//! - mutable: if the property can be changed;
+//! - nullable: if the property can be empty;
+//! - reference: if the property is a reference to the value.
+//!
+//! The type can have a tag as a hint, like `!ref ModelOne` to
+//! refer to another model, or `!enum [A, B]` for an enumeration.
+//!
+//! The comments before a model or a property are used as
+//! documentation, when the `doc_comment` key is not defined.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use monkey_common::src_ir::{
+    BagMetaNode, BagNode, BoolMetaNode, CharMetaNode, CharNode, FieldNode, ListType, MapType,
+    MetaNode, NumberMetaNode, NumberNode, ObjMetaNode, RefMetaNode, SrcNode, VisibilityMeta,
+};
+use monkey_yaml::parser::tokens::{YamlNode, YamlObject};
+use monkey_yaml::span::Span;
+
+use crate::DataModel;
+
+/// Error found while lowering the model, with the position
+/// of the node that cause it when it is known.
+#[derive(Debug)]
+pub struct SchemaError {
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+impl Error for SchemaError {}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{} at {}", self.message, span),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl From<SchemaError> for io::Error {
+    fn from(err: SchemaError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Lower the data model given in input in a list of
+/// Object nodes, one for each model defined.
+pub fn lower_model(model: &DataModel) -> Result<Vec<ObjMetaNode>, io::Error> {
+    let objs = match model {
+        DataModel::Yaml(ir) | DataModel::Json(ir) => lower_yaml(ir)?,
+        DataModel::JsonSchema => {
+            return Err(schema_error("json schema data model is not supported yet").into())
+        }
+    };
+    Ok(objs)
+}
+
+fn lower_yaml(ir: &[YamlNode]) -> Result<Vec<ObjMetaNode>, SchemaError> {
+    let mut models = vec![];
+    for node in ir {
+        models.append(&mut entries(node)?);
+    }
+    let names: Vec<&str> = models.iter().map(|model| model.key).collect();
+    models
+        .iter()
+        .map(|model| lower_obj(model, &names))
+        .collect()
+}
+
+/// Key value entry of a YAML node, with the span of the whole
+/// entry and the comments before it.
+struct Entry<'a> {
+    key: &'a str,
+    span: Span,
+    value: &'a YamlNode,
+    doc: Option<String>,
+}
+
+/// Collect the key value entries of a YAML node, the entries
+/// can be defined as a mapping or as a list of mapping like
+/// `- type: "int64"`.
+///
+/// An item of the list can define more entries, e.g: the ones
+/// merged by `- <<: *audit`, and each of them is an entry of the
+/// node. An entry defined again by a following item takes the
+/// place of the previous one, so the merged entries can be
+/// overridden.
+///
+/// The aliases are resolved to the node with the anchor, and the
+/// comments of a list item are joined to the comments of its entry.
+fn entries(node: &YamlNode) -> Result<Vec<Entry<'_>>, SchemaError> {
+    let is_list = matches!(node.resolve().object, YamlObject::List(_));
+    let nodes = match &node.resolve().object {
+        YamlObject::Mapping(entries, _) => {
+            return entries
+                .iter()
+                .map(|entry| match entry.key_str() {
+                    Some(key) => Ok(Entry {
+                        key,
+                        span: entry.span(),
+                        value: &entry.value,
+                        doc: entry.comments.doc(),
+                    }),
+                    None => Err(schema_error_at(
+                        &format!("expected a string key but found {:?}", entry.key.object),
+                        entry.key.span,
+                    )),
+                })
+                .collect();
+        }
+        YamlObject::List(nodes) | YamlObject::Document(nodes) => nodes,
+        YamlObject::Comment(_) => return Ok(vec![]),
+        object => {
+            return Err(schema_error_at(
+                &format!("expected a mapping but found {:?}", object),
+                node.span,
+            ))
+        }
+    };
+    let mut entries_found = vec![];
+    for item in nodes {
+        let mut item_entries = entries(item)?;
+        if let (Some(item_doc), Some(entry)) = (item.comments.doc(), item_entries.first_mut()) {
+            entry.doc = Some(match &entry.doc {
+                Some(doc) => format!("{item_doc}\n{doc}"),
+                None => item_doc,
+            });
+        }
+        for entry in item_entries {
+            let found = entries_found
+                .iter()
+                .position(|found: &Entry| is_list && found.key == entry.key);
+            match found {
+                Some(pos) => entries_found[pos] = entry,
+                None => entries_found.push(entry),
+            }
+        }
+    }
+    Ok(entries_found)
+}
+
+fn lower_obj(model: &Entry, models: &[&str]) -> Result<ObjMetaNode, SchemaError> {
+    let name = model.key;
+    let mut fields = vec![];
+    for prop in entries(model.value)? {
+        let node = lower_prop(&prop, models).map_err(|err| SchemaError {
+            message: format!("model `{name}`, property `{}`: {}", prop.key, err.message),
+            span: err.span,
+        })?;
+        fields.push(FieldNode {
+            name: prop.key.to_string(),
+            node,
+        });
+    }
+    let metadata = MetaNode {
+        comment: model.doc.clone(),
+        ..MetaNode::default()
+    };
+    Ok(ObjMetaNode::new(name, metadata, fields))
+}
+
+fn lower_prop(prop: &Entry, models: &[&str]) -> Result<Box<dyn SrcNode>, SchemaError> {
+    let mut metadata = MetaNode {
+        comment: prop.doc.clone(),
+        ..MetaNode::default()
+    };
+    let mut tipe = None;
+    for Entry {
+        key,
+        span: key_span,
+        value,
+        ..
+    } in entries(prop.value)?
+    {
+        match key {
+            "doc_comment" => metadata.comment = Some(as_str(key, value)?.to_string()),
+            "type" => tipe = Some(value),
+            "visibility" => {
+                metadata.visibility = Some(match as_str(key, value)? {
+                    "public" => VisibilityMeta::Public,
+                    "private" => VisibilityMeta::Private,
+                    custom => VisibilityMeta::Custom(custom.to_string()),
+                })
+            }
+            "mutable" => metadata.mutability = Some(as_bool(key, value)?),
+            "nullable" => metadata.nullable = Some(as_bool(key, value)?),
+            "reference" => metadata.pointer = Some(as_bool(key, value)?),
+            _ => return Err(schema_error_at(&format!("unknown key `{key}`"), key_span)),
+        }
+    }
+    let Some(value) = tipe else {
+        return Err(schema_error_at("missing `type` key", prop.span));
+    };
+    let node: Box<dyn SrcNode> = match value.tag() {
+        Some("!ref") => match as_str("type", value)? {
+            tipe if models.contains(&tipe) => Box::new(RefMetaNode::new(metadata, tipe)),
+            tipe => {
+                return Err(schema_error_at(
+                    &format!("unknown model `{tipe}`"),
+                    value.span,
+                ))
+            }
+        },
+        Some("!enum") => Box::new(BagMetaNode::new(metadata, lower_enum(value)?)),
+        Some(tag) => {
+            return Err(schema_error_at(
+                &format!("unknown tag `{tag}` for `type`"),
+                value.span,
+            ))
+        }
+        None => {
+            lower_type(as_str("type", value)?, metadata, models).map_err(|err| SchemaError {
+                span: err.span.or(Some(value.span)),
+                ..err
+            })?
+        }
+    };
+    Ok(node)
+}
+
+/// Lower the enumeration defined with the `!enum` tag,
+/// e.g: `!enum [Active, Disabled]`.
+fn lower_enum(value: &YamlNode) -> Result<BagNode, SchemaError> {
+    let YamlObject::List(items) = &value.resolve().object else {
+        return Err(schema_error_at(
+            "expected a sequence of names for `!enum`",
+            value.span,
+        ));
+    };
+    let variants = items
+        .iter()
+        .map(|item| as_str("!enum", item).map(|variant| variant.to_string()))
+        .collect::<Result<Vec<String>, SchemaError>>()?;
+    Ok(BagNode::Enum(variants))
+}
+
+/// Lower the type name in the correct source node.
+fn lower_type(
+    tipe: &str,
+    metadata: MetaNode,
+    models: &[&str],
+) -> Result<Box<dyn SrcNode>, SchemaError> {
+    let node: Box<dyn SrcNode> = match tipe {
+        "string" => Box::new(CharMetaNode::new(metadata, CharNode::Str)),
+        "char" => Box::new(CharMetaNode::new(metadata, CharNode::Char)),
+        "int" => Box::new(NumberMetaNode::new(metadata, NumberNode::IntNode)),
+        "int32" => Box::new(NumberMetaNode::new(metadata, NumberNode::Int32Node)),
+        "int64" => Box::new(NumberMetaNode::new(metadata, NumberNode::Int64Node)),
+        "float32" => Box::new(NumberMetaNode::new(metadata, NumberNode::Float32Node)),
+        "float" | "float64" | "double" => {
+            Box::new(NumberMetaNode::new(metadata, NumberNode::Float64Node))
+        }
+        "bool" | "boolean" => Box::new(BoolMetaNode::new(metadata)),
+        _ => match lower_bag(tipe, models)? {
+            Some(bag) => Box::new(BagMetaNode::new(metadata, bag)),
+            None if models.contains(&tipe) => Box::new(RefMetaNode::new(metadata, tipe)),
+            None => return Err(schema_error(&format!("unknown type `{tipe}`"))),
+        },
+    };
+    Ok(node)
+}
+
+/// Lower a collection type like `[]int64`, `list[int64]`
+/// or `map[string]int64`, and return None if the type is
+/// not a collection.
+fn lower_bag(tipe: &str, models: &[&str]) -> Result<Option<BagNode>, SchemaError> {
+    let list_elem = tipe
+        .strip_prefix("[]")
+        .or_else(|| tipe.strip_prefix("list[")?.strip_suffix(']'));
+    if let Some(elem) = list_elem {
+        let (type_name, complex_type) = lower_elem(elem, models)?;
+        return Ok(Some(BagNode::ListNode(ListType {
+            type_name,
+            complex_type: Box::new(complex_type),
+        })));
+    }
+
+    if let Some(map_type) = tipe.strip_prefix("map[") {
+        let (key_type, value_type) = map_type
+            .split_once(']')
+            .ok_or_else(|| schema_error(&format!("malformed map type `{tipe}`")))?;
+        if !is_primitive(key_type) {
+            return Err(schema_error(&format!("invalid map key type `{key_type}`")));
+        }
+        let (value_type, complex_value) = lower_elem(value_type, models)?;
+        return Ok(Some(BagNode::MapNode(MapType {
+            key_type: key_type.to_string(),
+            value_type,
+            complex_value: Box::new(complex_value),
+        })));
+    }
+    Ok(None)
+}
+
+/// Lower the element type of a collection, that can be a
+/// type name or another collection.
+fn lower_elem(
+    elem: &str,
+    models: &[&str],
+) -> Result<(Option<String>, Option<BagNode>), SchemaError> {
+    if let Some(bag) = lower_bag(elem, models)? {
+        return Ok((None, Some(bag)));
+    }
+    if !is_primitive(elem) && !models.contains(&elem) {
+        return Err(schema_error(&format!("unknown type `{elem}`")));
+    }
+    Ok((Some(elem.to_string()), None))
+}
+
+/// Check if the type name is a primitive type of the schema.
+pub fn is_primitive(tipe: &str) -> bool {
+    matches!(
+        tipe,
+        "string"
+            | "char"
+            | "int"
+            | "int32"
+            | "int64"
+            | "float32"
+            | "float"
+            | "float64"
+            | "double"
+            | "bool"
+            | "boolean"
+    )
+}
+
+fn as_str<'a>(key: &str, value: &'a YamlNode) -> Result<&'a str, SchemaError> {
+    match &value.resolve().object {
+        YamlObject::Str(content) => Ok(content),
+        object => Err(schema_error_at(
+            &format!("expected a string for `{key}` but found {:?}", object),
+            value.span,
+        )),
+    }
+}
+
+fn as_bool(key: &str, value: &YamlNode) -> Result<bool, SchemaError> {
+    match &value.resolve().object {
+        YamlObject::Bool(content) => Ok(*content),
+        object => Err(schema_error_at(
+            &format!("expected a boolean for `{key}` but found {:?}", object),
+            value.span,
+        )),
+    }
+}
+
+/// wrapping the way to build a schema error
+fn schema_error(cause: &str) -> SchemaError {
+    SchemaError {
+        message: cause.to_string(),
+        span: None,
+    }
+}
+
+/// wrapping the way to build a schema error that point
+/// to the node at the span given in input.
+fn schema_error_at(cause: &str, span: Span) -> SchemaError {
+    SchemaError {
+        message: cause.to_string(),
+        span: Some(span),
+    }
+}
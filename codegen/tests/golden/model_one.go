@@ -0,0 +1,10 @@
+// Code generated by monkyc. DO NOT EDIT.
+
+package model
+
+type ModelOne struct {
+	// Documentation document
+	PropOne *int64    `json:"PropOne,omitempty"`
+	// This is a prop that contains a custom type
+	PropTwo *ModelOne `json:"PropTwo,omitempty"`
+}
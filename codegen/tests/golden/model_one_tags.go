@@ -0,0 +1,10 @@
+// Code generated by monkyc. DO NOT EDIT.
+
+package model
+
+type ModelOne struct {
+	// Documentation document
+	PropOne *int64    `json:"prop_one,omitempty" yaml:"prop_one,omitempty"`
+	// This is a prop that contains a custom type
+	PropTwo *ModelOne `json:"prop_two,omitempty" yaml:"prop_two,omitempty"`
+}
@@ -0,0 +1,13 @@
+// Code generated by monkyc. DO NOT EDIT.
+
+package model
+
+type ModelTwo struct {
+	// Name of the user
+	UserName  string `json:"user_name"`
+	userScore float64
+	// Tags of the user
+	Tags      []string              `json:"tags,omitempty"`
+	Friends   map[string][]ModelTwo `json:"friends"`
+	Active    *bool                 `json:"active"`
+}
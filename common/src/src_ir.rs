@@ -11,12 +11,16 @@ use super::src_visit::OutVisitor;
 /// Source code node that contains all the information
 /// useful to encode a source code metadata
 pub trait SrcNode {
+    /// Metadata attached to the node
+    fn metadata(&self) -> &MetaNode;
+
     /// Give the possibility to the node to
     /// to be traversed from a OutVisitor
     fn accept(&self, visitor: &mut dyn OutVisitor);
 }
 
 /// Visibility enum to add metadata about the visibility
+#[derive(Debug, Clone, PartialEq)]
 pub enum VisibilityMeta {
     Public,
     Private,
@@ -28,96 +32,170 @@ pub enum VisibilityMeta {
 /// Metadata Node, a struct that contains all the metadata
 /// useful for the code generator, like comment over a node
 /// public/private, ecc.
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct MetaNode {
     /// Comment metadata
-    comment: Option<String>,
+    pub comment: Option<String>,
     /// Visibility of the node in the target language
-    visibility: Option<VisibilityMeta>,
+    pub visibility: Option<VisibilityMeta>,
     /// Mutability of the node in the target language
-    mutability: Option<bool>,
-    nullable: Option<bool>,
-    pointer: Option<bool>,
+    pub mutability: Option<bool>,
+    pub nullable: Option<bool>,
+    pub pointer: Option<bool>,
+}
+
+/// Field of a obj/struct, it contains the name of the
+/// field and the node that define the type of it.
+pub struct FieldNode {
+    pub name: String,
+    pub node: Box<dyn SrcNode>,
 }
 
 /// Concrete implementation of a obj/struct data model
-pub struct ObjMetaNode<'a> {
-    metadata: &'a MetaNode,
+pub struct ObjMetaNode {
+    pub name: String,
+    pub metadata: MetaNode,
+    pub fields: Vec<FieldNode>,
 }
 
 /// Custom interface of the ObjMetaNode
-impl<'a> ObjMetaNode<'a> {
-    fn new(metadata: &'a MetaNode) -> Self {
-        ObjMetaNode { metadata }
+impl ObjMetaNode {
+    pub fn new(name: &str, metadata: MetaNode, fields: Vec<FieldNode>) -> Self {
+        ObjMetaNode {
+            name: name.to_string(),
+            metadata,
+            fields,
+        }
     }
 }
 
 /// Common interface SrcNode implemented over the ObjMetaNode
-impl SrcNode for ObjMetaNode<'_> {
+impl SrcNode for ObjMetaNode {
+    fn metadata(&self) -> &MetaNode {
+        &self.metadata
+    }
+
     fn accept(&self, visitor: &mut dyn OutVisitor) {
         visitor.visit_obj(self);
     }
 }
 
+/// Reference to another obj/struct defined inside the
+/// data model, e.g: a field that has a custom type.
+pub struct RefMetaNode {
+    pub metadata: MetaNode,
+    pub name: String,
+}
+
+impl RefMetaNode {
+    pub fn new(metadata: MetaNode, name: &str) -> Self {
+        RefMetaNode {
+            metadata,
+            name: name.to_string(),
+        }
+    }
+}
+
+impl SrcNode for RefMetaNode {
+    fn metadata(&self) -> &MetaNode {
+        &self.metadata
+    }
+
+    fn accept(&self, visitor: &mut dyn OutVisitor) {
+        visitor.visit_ref(self);
+    }
+}
+
 /// Concrete implementation of a string data model
 pub enum CharNode {
-    Char(char),
-    Str(String),
+    Char,
+    Str,
 }
 
-pub struct CharMetaNode<'a> {
-    metadata: &'a MetaNode,
-    tipe: &'a CharNode,
+pub struct CharMetaNode {
+    pub metadata: MetaNode,
+    pub tipe: CharNode,
 }
 
-impl<'a> CharMetaNode<'a> {
-    fn new(metadata: &'a MetaNode, tipe: &'a CharNode) -> Self {
-        CharMetaNode {
-            metadata: metadata,
-            tipe: tipe,
-        }
+impl CharMetaNode {
+    pub fn new(metadata: MetaNode, tipe: CharNode) -> Self {
+        CharMetaNode { metadata, tipe }
     }
 }
 
-impl SrcNode for CharMetaNode<'_> {
+impl SrcNode for CharMetaNode {
+    fn metadata(&self) -> &MetaNode {
+        &self.metadata
+    }
+
     fn accept(&self, visitor: &mut dyn OutVisitor) {
         visitor.visit_char(self);
     }
 }
 
 pub enum NumberNode {
-    Int64Node(i64),
-    Float64Node(f64),
+    /// Integer with the size of the target platform
+    IntNode,
+    Int32Node,
+    Int64Node,
+    Float32Node,
+    Float64Node,
 }
 
-pub struct NumberMetaNode<'a> {
-    metadata: &'a MetaNode,
-    tipe: &'a NumberNode,
+pub struct NumberMetaNode {
+    pub metadata: MetaNode,
+    pub tipe: NumberNode,
 }
 
-impl<'a> NumberMetaNode<'a> {
-    fn new(metadata: &'a MetaNode, tipe: &'a NumberNode) -> Self {
+impl NumberMetaNode {
+    pub fn new(metadata: MetaNode, tipe: NumberNode) -> Self {
         NumberMetaNode { metadata, tipe }
     }
 }
 
-impl SrcNode for NumberMetaNode<'_> {
+impl SrcNode for NumberMetaNode {
+    fn metadata(&self) -> &MetaNode {
+        &self.metadata
+    }
+
     fn accept(&self, visitor: &mut dyn OutVisitor) {
         visitor.visit_number(self);
     }
 }
 
+/// Boolean data model
+pub struct BoolMetaNode {
+    pub metadata: MetaNode,
+}
+
+impl BoolMetaNode {
+    pub fn new(metadata: MetaNode) -> Self {
+        BoolMetaNode { metadata }
+    }
+}
+
+impl SrcNode for BoolMetaNode {
+    fn metadata(&self) -> &MetaNode {
+        &self.metadata
+    }
+
+    fn accept(&self, visitor: &mut dyn OutVisitor) {
+        visitor.visit_bool(self);
+    }
+}
+
 /// ListType defintion
 pub struct ListType {
-    type_name: Option<String>,
-    complex_type: Box<Option<BagNode>>,
+    pub type_name: Option<String>,
+    pub complex_type: Box<Option<BagNode>>,
 }
 
 /// map type definition
 pub struct MapType {
-    key_type: String,
-    value_type: Option<String>,
+    pub key_type: String,
+    pub value_type: Option<String>,
     //TODO: Why the compiler suggest to  use Box?
-    complex_value: Box<Option<BagNode>>,
+    pub complex_value: Box<Option<BagNode>>,
 }
 
 /// Collection enum type
@@ -127,18 +205,22 @@ pub enum BagNode {
     Enum(Vec<String>),
 }
 
-pub struct BagMetaNode<'a> {
-    metadata: &'a MetaNode,
-    tipe: &'a BagNode,
+pub struct BagMetaNode {
+    pub metadata: MetaNode,
+    pub tipe: BagNode,
 }
 
-impl<'a> BagMetaNode<'a> {
-    fn new(metadata: &'a MetaNode, tipe: &'a BagNode) -> Self {
+impl BagMetaNode {
+    pub fn new(metadata: MetaNode, tipe: BagNode) -> Self {
         BagMetaNode { metadata, tipe }
     }
 }
 
-impl SrcNode for BagMetaNode<'_> {
+impl SrcNode for BagMetaNode {
+    fn metadata(&self) -> &MetaNode {
+        &self.metadata
+    }
+
     fn accept(&self, visitor: &mut dyn OutVisitor) {
         visitor.visit_collection(self);
     }
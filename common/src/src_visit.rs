@@ -3,7 +3,9 @@
 //! Give the possibility to a SrcNode
 //!
 //! author: https://github.com/vincenzopalazzo
-use super::src_ir::{BagMetaNode, CharMetaNode, NumberMetaNode, ObjMetaNode};
+use super::src_ir::{
+    BagMetaNode, BoolMetaNode, CharMetaNode, NumberMetaNode, ObjMetaNode, RefMetaNode,
+};
 
 /// Out Visitor traits that contains all the
 /// method to implement a new type of visit
@@ -11,6 +13,10 @@ pub trait OutVisitor {
     /// Core implementation logic to visit a Object
     fn visit_obj(&mut self, obj: &ObjMetaNode);
 
+    /// Core implementation logic to visit a reference
+    /// to another Object
+    fn visit_ref(&mut self, reference: &RefMetaNode);
+
     /// Core implementation logic to visit a char type
     /// included strings.
     fn visit_char(&mut self, string: &CharMetaNode);
@@ -18,6 +24,9 @@ pub trait OutVisitor {
     /// Core implementation logic to visit a number
     fn visit_number(&mut self, int: &NumberMetaNode);
 
+    /// Core implementation logic to visit a boolean
+    fn visit_bool(&mut self, boolean: &BoolMetaNode);
+
     /// Core implementation logic to visit a collection
     fn visit_collection(&mut self, bag: &BagMetaNode);
 }
@@ -0,0 +1,200 @@
+//! JSON format parser, RFC 8259, that lower the content
+//! in the same tree of nodes produced by the YAML parser,
+//! so the code generator works on both the formats.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::error::Error;
+use std::fmt;
+use std::fmt::Formatter;
+
+use monkey_yaml::parser::tokens::{YamlNode, YamlObject};
+use monkey_yaml::span::Span;
+
+pub mod parser;
+pub mod scanner;
+pub mod tokens;
+
+use parser::Parser;
+use scanner::Scanner;
+use tokens::JsonToken;
+
+/// Kind of error that the scanner or the parser
+/// can find in the content
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonErrorKind {
+    /// Character that is not part of a valid token
+    UnexpectedChar(char),
+    /// Name that is not `true`, `false` or `null`
+    UnknownLiteral(String),
+    /// String without the closing quote
+    UnterminatedString,
+    /// Escape sequence not supported by JSON, e.g: `\q`
+    InvalidEscape(char),
+    /// `\u` escape sequence that is not a valid char, e.g:
+    /// a surrogate without its pair
+    InvalidUnicode,
+    /// Control character inside a string, that must be escaped
+    ControlChar(char),
+    /// Number that is not well formed or that can not be
+    /// represented, e.g: `01` or `1e999`
+    InvalidNumber,
+    /// Token that is not valid in the current position
+    UnexpectedToken,
+    /// The content ended before the end of the value
+    UnexpectedEof,
+    /// Key defined more than once in the same object
+    DuplicateKey,
+    /// Collections nested more than the max depth
+    DepthLimitExceeded,
+}
+
+impl fmt::Display for JsonErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonErrorKind::UnexpectedChar(char_at) => {
+                write!(f, "unexpected character `{}`", char_at.escape_debug())
+            }
+            JsonErrorKind::UnknownLiteral(literal) => write!(f, "unknown literal `{}`", literal),
+            JsonErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            JsonErrorKind::InvalidEscape(escape) => {
+                write!(f, "invalid escape sequence `\\{}`", escape.escape_debug())
+            }
+            JsonErrorKind::InvalidUnicode => write!(f, "invalid unicode escape sequence"),
+            JsonErrorKind::ControlChar(char_at) => {
+                write!(
+                    f,
+                    "control character `{}` in string",
+                    char_at.escape_debug()
+                )
+            }
+            JsonErrorKind::InvalidNumber => write!(f, "invalid number"),
+            JsonErrorKind::UnexpectedToken => write!(f, "unexpected token"),
+            JsonErrorKind::UnexpectedEof => write!(f, "unexpected end of file"),
+            JsonErrorKind::DuplicateKey => write!(f, "duplicate key"),
+            JsonErrorKind::DepthLimitExceeded => write!(f, "depth limit exceeded"),
+        }
+    }
+}
+
+/// Error with the position of the content where it is
+/// found, and the token found when it is reported by
+/// the parser.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonError {
+    pub kind: JsonErrorKind,
+    /// what was expected, e.g: "`:`"
+    pub expected: Option<String>,
+    /// the token found in the stream
+    pub found: Option<JsonToken>,
+    /// position of the content that cause the error
+    pub span: Span,
+}
+
+impl JsonError {
+    pub fn new(
+        kind: JsonErrorKind,
+        expected: Option<&str>,
+        found: Option<JsonToken>,
+        span: Span,
+    ) -> Self {
+        JsonError {
+            kind,
+            expected: expected.map(|expected| expected.to_string()),
+            found,
+            span,
+        }
+    }
+
+    /// Message of the error without the position, e.g:
+    /// "unexpected `]`, expected a value".
+    pub fn message(&self) -> String {
+        let mut message = match (&self.kind, &self.found) {
+            (JsonErrorKind::UnexpectedToken, Some(found)) => format!("unexpected {}", found),
+            (JsonErrorKind::DuplicateKey, Some(found)) => format!("{} {}", self.kind, found),
+            _ => self.kind.to_string(),
+        };
+        if let Some(expected) = &self.expected {
+            message.push_str(&format!(", expected {}", expected));
+        }
+        message
+    }
+}
+
+impl Error for JsonError {}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.message(), self.span)
+    }
+}
+
+/// Parse the JSON content, and return a single `YamlObject::Document`
+/// node with the value, like the YAML parser does for a stream with
+/// one document, e.g:
+///
+/// let documents = monkey_json::parse_str(r#"{"name": "Mark"}"#)?;
+pub fn parse_str(content: &str) -> Result<Vec<YamlNode>, JsonError> {
+    let tokens = Scanner::new(content).scan()?;
+    let root = Parser::new(&tokens).parse()?;
+    let span = root.span;
+    Ok(vec![YamlNode::new(YamlObject::Document(vec![root]), span)])
+}
+
+#[cfg(test)]
+mod test {
+    use monkey_yaml::parser::tokens::{YamlNode, YamlObject};
+    use monkey_yaml::ParseOptions;
+
+    use crate::parse_str;
+
+    /// Shape of the node without the spans, the comments and the
+    /// style of the collections, that depend on the format.
+    fn tree(node: &YamlNode) -> String {
+        match &node.object {
+            YamlObject::Document(nodes) | YamlObject::List(nodes) => {
+                let nodes: Vec<String> = nodes
+                    .iter()
+                    .filter(|node| !matches!(node.object, YamlObject::Comment(_)))
+                    .map(tree)
+                    .collect();
+                format!("[{}]", nodes.join(", "))
+            }
+            YamlObject::Mapping(entries, _) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|entry| format!("{}: {}", tree(&entry.key), tree(&entry.value)))
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            object => format!("{:?}", object),
+        }
+    }
+
+    /// Parse the JSON and the YAML content, and check
+    /// that they have the same tree.
+    fn assert_same_tree(json: &str, yaml: &str) {
+        let json: Vec<String> = parse_str(json).unwrap().iter().map(tree).collect();
+        let yaml: Vec<String> = monkey_yaml::parse_str(yaml, ParseOptions::new())
+            .unwrap()
+            .iter()
+            .map(tree)
+            .collect();
+        assert_eq!(json, yaml);
+    }
+
+    #[test]
+    fn parse_str_same_tree_of_yaml() {
+        assert_same_tree(
+            r#"{"name": "Mark", "hr": 65, "avg": 0.5, "tags": ["a", null, true]}"#,
+            "name: Mark\nhr: 65\navg: 0.5\ntags: [a, null, true]\n",
+        );
+    }
+
+    #[test]
+    fn parse_model_one_same_tree_of_yaml() {
+        assert_same_tree(
+            include_str!("../../examples/model_one.json"),
+            include_str!("../../examples/model_one.yml"),
+        );
+    }
+}
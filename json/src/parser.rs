@@ -0,0 +1,258 @@
+//! Json parser that lower the tokens in the same tree of
+//! nodes produced by the Yaml parser, where an object is a
+//! flow mapping and an array is a list, e.g:
+//!
+//! {"name": ["Mark"]}
+//!
+//! is the same tree of `{name: [Mark]}`.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::collections::HashSet;
+
+use albert_stream::{BasicStream, Stream};
+use monkey_yaml::parser::options::DEFAULT_MAX_DEPTH;
+use monkey_yaml::parser::tokens::{MappingEntry, YamlNode, YamlObject};
+use monkey_yaml::span::Span;
+
+use crate::tokens::{JsonToken, Token};
+use crate::{JsonError, JsonErrorKind};
+
+/// Recursive descent parser over the stream of tokens,
+/// the stream has to end with the EOF token.
+pub struct Parser {
+    stream: BasicStream<Token>,
+    /// number of collections that we are parsing
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Parser {
+    pub fn new(tokens: &[Token]) -> Self {
+        Parser::new_with_max_depth(tokens, DEFAULT_MAX_DEPTH)
+    }
+
+    pub fn new_with_max_depth(tokens: &[Token], max_depth: usize) -> Self {
+        Parser {
+            stream: BasicStream::new(tokens),
+            depth: 0,
+            max_depth,
+        }
+    }
+
+    /// Parse the value of the content, where the
+    /// content after the value is an error.
+    pub fn parse(&mut self) -> Result<YamlNode, JsonError> {
+        let node = self.parse_value()?;
+        if self.peek().kind != JsonToken::EOF {
+            return Err(self.unexpected(Some("end of file")));
+        }
+        Ok(node)
+    }
+
+    fn parse_value(&mut self) -> Result<YamlNode, JsonError> {
+        let token = self.peek().clone();
+        let object = match token.kind {
+            JsonToken::LeftCurlyBrace => return self.parse_object(),
+            JsonToken::LeftSquareBrace => return self.parse_array(),
+            JsonToken::StringVal(content) => YamlObject::Str(content),
+            JsonToken::IntVal(number) => YamlObject::Int(number),
+            JsonToken::FloatVal(number) => YamlObject::Float(number),
+            JsonToken::BoolVal(value) => YamlObject::Bool(value),
+            JsonToken::NullVal => YamlObject::Null,
+            _ => return Err(self.unexpected(Some("a value"))),
+        };
+        self.stream.advance();
+        Ok(YamlNode::new(object, token.span))
+    }
+
+    /// Parse an object in a flow mapping with the entries in
+    /// the order of definition, and reject the duplicate keys.
+    fn parse_object(&mut self) -> Result<YamlNode, JsonError> {
+        let start = self.start_node()?;
+        let mut entries = vec![];
+        let mut keys = HashSet::new();
+        let end = match self.eat(JsonToken::RightCurlyBrace) {
+            Some(end) => end,
+            None => loop {
+                let token = self.peek().clone();
+                let JsonToken::StringVal(key) = &token.kind else {
+                    return Err(self.unexpected(Some("a string key")));
+                };
+                if !keys.insert(key.clone()) {
+                    return Err(JsonError::new(
+                        JsonErrorKind::DuplicateKey,
+                        None,
+                        Some(token.kind),
+                        token.span,
+                    ));
+                }
+                self.stream.advance();
+                let key = YamlNode::new(YamlObject::Str(key.clone()), token.span);
+                self.expect(JsonToken::Colon, "`:`")?;
+                let value = self.parse_value()?;
+                entries.push(MappingEntry::new(key, value));
+                if self.eat(JsonToken::Comma).is_none() {
+                    break self.expect(JsonToken::RightCurlyBrace, "`,` or `}`")?;
+                }
+            },
+        };
+        self.depth -= 1;
+        Ok(YamlNode::new(
+            YamlObject::Mapping(entries, true),
+            start.merge(&end),
+        ))
+    }
+
+    fn parse_array(&mut self) -> Result<YamlNode, JsonError> {
+        let start = self.start_node()?;
+        let mut items = vec![];
+        let end = match self.eat(JsonToken::RightSquareBrace) {
+            Some(end) => end,
+            None => loop {
+                items.push(self.parse_value()?);
+                if self.eat(JsonToken::Comma).is_none() {
+                    break self.expect(JsonToken::RightSquareBrace, "`,` or `]`")?;
+                }
+            },
+        };
+        self.depth -= 1;
+        Ok(YamlNode::new(YamlObject::List(items), start.merge(&end)))
+    }
+
+    /// Consume the token that open a collection, and return its
+    /// span, the collections nested more than the max depth are
+    /// an error.
+    fn start_node(&mut self) -> Result<Span, JsonError> {
+        let span = self.peek().span;
+        if self.depth >= self.max_depth {
+            return Err(JsonError::new(
+                JsonErrorKind::DepthLimitExceeded,
+                Some(&format!("at most {} nested collections", self.max_depth)),
+                Some(self.peek().kind.clone()),
+                span,
+            ));
+        }
+        self.depth += 1;
+        self.stream.advance();
+        Ok(span)
+    }
+
+    fn peek(&self) -> &Token {
+        self.stream.peek()
+    }
+
+    /// Consume the current token if it is the one given in
+    /// input, and return its span.
+    fn eat(&mut self, kind: JsonToken) -> Option<Span> {
+        if self.peek().kind != kind {
+            return None;
+        }
+        Some(self.stream.advance().span)
+    }
+
+    fn expect(&mut self, kind: JsonToken, expected: &str) -> Result<Span, JsonError> {
+        self.eat(kind)
+            .ok_or_else(|| self.unexpected(Some(expected)))
+    }
+
+    fn unexpected(&self, expected: Option<&str>) -> JsonError {
+        let token = self.peek();
+        let kind = match token.kind {
+            JsonToken::EOF => JsonErrorKind::UnexpectedEof,
+            _ => JsonErrorKind::UnexpectedToken,
+        };
+        JsonError::new(kind, expected, Some(token.kind.clone()), token.span)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use monkey_yaml::parser::tokens::{YamlNode, YamlObject};
+
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+    use crate::{JsonError, JsonErrorKind};
+
+    fn parse(content: &str) -> Result<YamlNode, JsonError> {
+        let tokens = Scanner::new(content).scan()?;
+        Parser::new(&tokens).parse()
+    }
+
+    #[test]
+    fn parse_object() {
+        let node =
+            parse(r#"{"name": "Mark", "hr": 65, "avg": 0.278, "tags": [true, null]}"#).unwrap();
+        let YamlObject::Mapping(entries, true) = &node.object else {
+            panic!("expected a flow mapping, found {:?}", node.object);
+        };
+        let keys: Vec<&str> = entries.iter().filter_map(|entry| entry.key_str()).collect();
+        assert_eq!(keys, vec!["name", "hr", "avg", "tags"]);
+        assert_eq!(
+            node.get("name").and_then(|name| name.as_str()),
+            Some("Mark")
+        );
+        assert!(matches!(
+            node.get("hr").unwrap().object,
+            YamlObject::Int(65)
+        ));
+        let YamlObject::List(items) = &node.get("tags").unwrap().object else {
+            panic!("expected a list");
+        };
+        assert!(matches!(items[1].object, YamlObject::Null));
+        assert_eq!((node.span.start, node.span.end), (0, 62));
+
+        assert!(
+            matches!(parse("[]").unwrap().object, YamlObject::List(ref items) if items.is_empty())
+        );
+        assert!(matches!(parse(" 12 ").unwrap().object, YamlObject::Int(12)));
+    }
+
+    #[test]
+    fn parse_errors() {
+        for (content, expected) in [
+            (
+                r#"{"name" "Mark"}"#,
+                "unexpected `\"Mark\"`, expected `:` at line 1, column 9",
+            ),
+            (
+                "[1, 2,]",
+                "unexpected `]`, expected a value at line 1, column 7",
+            ),
+            (
+                "{name: 1}",
+                "unknown literal `name`, expected `true`, `false` or `null` at line 1, column 2",
+            ),
+            (
+                "{\"a\": 1} {}",
+                "unexpected `{`, expected end of file at line 1, column 10",
+            ),
+            (
+                "[1, 2",
+                "unexpected end of file, expected `,` or `]` at line 1, column 6",
+            ),
+            (
+                "",
+                "unexpected end of file, expected a value at line 1, column 1",
+            ),
+            (
+                "{\"a\": 1,\n \"a\": 2}",
+                "duplicate key `\"a\"` at line 2, column 2",
+            ),
+        ] {
+            assert_eq!(parse(content).unwrap_err().to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn parse_max_depth() {
+        let tokens = Scanner::new("[[{\"a\": []}]]").scan().unwrap();
+        assert!(Parser::new_with_max_depth(&tokens, 4).parse().is_ok());
+        let err = Parser::new_with_max_depth(&tokens, 3).parse().unwrap_err();
+        assert_eq!(err.kind, JsonErrorKind::DepthLimitExceeded);
+        assert_eq!(err.span.column, 9);
+
+        // the nesting does not overflow the stack
+        let content = "[".repeat(100_000);
+        assert!(parse(&content).is_err());
+    }
+}
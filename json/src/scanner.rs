@@ -0,0 +1,400 @@
+//! Json scanner that split the content in a list of
+//! tokens, with the position of each token, e.g:
+//!
+//! {"name": "Mark"}
+//!
+//! is `{`, `"name"`, `:`, `"Mark"`, `}` and EOF.
+//!
+//! author: https://github.com/vincenzopalazzo
+use monkey_yaml::span::Span;
+
+use crate::tokens::{JsonToken, Token};
+use crate::{JsonError, JsonErrorKind};
+
+/// Scanner over the content, that keeps the line and the
+/// column of the current position for the spans.
+pub struct Scanner<'a> {
+    content: &'a str,
+    /// byte offset of the current position
+    pos: usize,
+    /// line of the current position, starting from 1
+    line: usize,
+    /// column of the current position, starting from 1
+    column: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(content: &'a str) -> Self {
+        Scanner {
+            content,
+            pos: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// Scan the whole content, the last token is always EOF.
+    pub fn scan(mut self) -> Result<Vec<Token>, JsonError> {
+        let mut tokens = vec![];
+        loop {
+            self.skip_whitespaces();
+            let start = self.position();
+            let Some(char_at) = self.peek() else {
+                tokens.push(Token::new(JsonToken::EOF, self.span(start)));
+                return Ok(tokens);
+            };
+            let kind = match char_at {
+                '{' => self.punct(JsonToken::LeftCurlyBrace),
+                '}' => self.punct(JsonToken::RightCurlyBrace),
+                '[' => self.punct(JsonToken::LeftSquareBrace),
+                ']' => self.punct(JsonToken::RightSquareBrace),
+                ':' => self.punct(JsonToken::Colon),
+                ',' => self.punct(JsonToken::Comma),
+                '"' => self.scan_string()?,
+                '-' | '0'..='9' => self.scan_number()?,
+                'a'..='z' => self.scan_literal()?,
+                _ => {
+                    self.bump();
+                    return Err(self.error(JsonErrorKind::UnexpectedChar(char_at), None, start));
+                }
+            };
+            tokens.push(Token::new(kind, self.span(start)));
+        }
+    }
+
+    fn punct(&mut self, kind: JsonToken) -> JsonToken {
+        self.bump();
+        kind
+    }
+
+    /// Scan a string between double quotes, and
+    /// decode the escape sequences.
+    fn scan_string(&mut self) -> Result<JsonToken, JsonError> {
+        let start = self.position();
+        // consume the opening quote
+        self.bump();
+        let mut content = String::new();
+        loop {
+            let escape = self.position();
+            match self.bump() {
+                None => {
+                    return Err(self.error(
+                        JsonErrorKind::UnterminatedString,
+                        Some("the closing quote"),
+                        start,
+                    ))
+                }
+                Some('"') => return Ok(JsonToken::StringVal(content)),
+                Some('\\') => content.push(self.scan_escape(escape)?),
+                Some(char_at) if (char_at as u32) < 0x20 => {
+                    return Err(self.error(
+                        JsonErrorKind::ControlChar(char_at),
+                        Some("an escape sequence"),
+                        escape,
+                    ))
+                }
+                Some(char_at) => content.push(char_at),
+            }
+        }
+    }
+
+    /// Decode the escape sequence after the `\`, where the
+    /// surrogate pairs of `\u` are joined in a single char.
+    fn scan_escape(&mut self, start: (usize, usize, usize)) -> Result<char, JsonError> {
+        let escaped = match self.bump() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let high = self.scan_hex(start)?;
+                let code = match high {
+                    0xD800..=0xDBFF => {
+                        if !self.content[self.pos..].starts_with("\\u") {
+                            return Err(self.error(JsonErrorKind::InvalidUnicode, None, start));
+                        }
+                        self.bump();
+                        self.bump();
+                        let low = self.scan_hex(start)?;
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return Err(self.error(JsonErrorKind::InvalidUnicode, None, start));
+                        }
+                        0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                    }
+                    code => code,
+                };
+                // a low surrogate without the high one is not a char
+                match char::from_u32(code) {
+                    Some(code) => code,
+                    None => return Err(self.error(JsonErrorKind::InvalidUnicode, None, start)),
+                }
+            }
+            Some(char_at) => {
+                return Err(self.error(JsonErrorKind::InvalidEscape(char_at), None, start))
+            }
+            None => {
+                return Err(self.error(
+                    JsonErrorKind::UnterminatedString,
+                    Some("the closing quote"),
+                    start,
+                ))
+            }
+        };
+        Ok(escaped)
+    }
+
+    /// Scan the 4 hex digits of a `\u` escape sequence.
+    fn scan_hex(&mut self, start: (usize, usize, usize)) -> Result<u32, JsonError> {
+        let digits = self.content[self.pos..].get(..4);
+        let Some(code) = digits.and_then(|digits| {
+            digits
+                .chars()
+                .all(|digit| digit.is_ascii_hexdigit())
+                .then(|| u32::from_str_radix(digits, 16).ok())
+                .flatten()
+        }) else {
+            return Err(self.error(JsonErrorKind::InvalidUnicode, Some("4 hex digits"), start));
+        };
+        for _ in 0..4 {
+            self.bump();
+        }
+        Ok(code)
+    }
+
+    /// Scan a number with the grammar of RFC 8259, e.g: `-1.5e3`,
+    /// the number without fraction and exponent is an integer
+    /// when it fits an i64.
+    fn scan_number(&mut self) -> Result<JsonToken, JsonError> {
+        let start = self.position();
+        self.eat('-');
+        match self.peek() {
+            Some('0') => {
+                self.bump();
+            }
+            Some('1'..='9') => self.eat_digits(),
+            _ => return Err(self.number_error(start)),
+        }
+        let mut integer = true;
+        if self.eat('.') {
+            integer = false;
+            if !self.peek().is_some_and(|digit| digit.is_ascii_digit()) {
+                return Err(self.number_error(start));
+            }
+            self.eat_digits();
+        }
+        if self.eat('e') || self.eat('E') {
+            integer = false;
+            if !self.eat('+') {
+                self.eat('-');
+            }
+            if !self.peek().is_some_and(|digit| digit.is_ascii_digit()) {
+                return Err(self.number_error(start));
+            }
+            self.eat_digits();
+        }
+        // the leading zeros and the numbers followed by
+        // a letter, e.g: `01` and `12px`, are not valid
+        if self.peek().is_some_and(|next| next.is_ascii_alphanumeric()) {
+            return Err(self.number_error(start));
+        }
+        let number = &self.content[start.0..self.pos];
+        if integer {
+            if let Ok(number) = number.parse::<i64>() {
+                return Ok(JsonToken::IntVal(number));
+            }
+        }
+        match number.parse::<f64>() {
+            Ok(number) if number.is_finite() => Ok(JsonToken::FloatVal(number)),
+            _ => Err(self.number_error(start)),
+        }
+    }
+
+    fn number_error(&mut self, start: (usize, usize, usize)) -> JsonError {
+        // the span of the error contains the whole word
+        while self
+            .peek()
+            .is_some_and(|next| next.is_ascii_alphanumeric() || "+-.".contains(next))
+        {
+            self.bump();
+        }
+        self.error(JsonErrorKind::InvalidNumber, None, start)
+    }
+
+    /// Scan the literal names `true`, `false` and `null`.
+    fn scan_literal(&mut self) -> Result<JsonToken, JsonError> {
+        let start = self.position();
+        while self.peek().is_some_and(|next| next.is_ascii_alphanumeric()) {
+            self.bump();
+        }
+        match &self.content[start.0..self.pos] {
+            "true" => Ok(JsonToken::BoolVal(true)),
+            "false" => Ok(JsonToken::BoolVal(false)),
+            "null" => Ok(JsonToken::NullVal),
+            literal => Err(self.error(
+                JsonErrorKind::UnknownLiteral(literal.to_owned()),
+                Some("`true`, `false` or `null`"),
+                start,
+            )),
+        }
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.bump();
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.content[self.pos..].chars().next()
+    }
+
+    /// Consume the current char and return it.
+    fn bump(&mut self) -> Option<char> {
+        let char_at = self.peek()?;
+        self.pos += char_at.len_utf8();
+        if char_at == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(char_at)
+    }
+
+    /// Consume the current char if it is the one given in input.
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.bump();
+            return true;
+        }
+        false
+    }
+
+    fn eat_digits(&mut self) {
+        while self.peek().is_some_and(|digit| digit.is_ascii_digit()) {
+            self.bump();
+        }
+    }
+
+    /// Byte offset, line and column of the current position.
+    fn position(&self) -> (usize, usize, usize) {
+        (self.pos, self.line, self.column)
+    }
+
+    /// Span from the position given in input to the current one.
+    fn span(&self, (start, line, column): (usize, usize, usize)) -> Span {
+        Span::new(start, self.pos, line, column)
+    }
+
+    fn error(
+        &self,
+        kind: JsonErrorKind,
+        expected: Option<&str>,
+        start: (usize, usize, usize),
+    ) -> JsonError {
+        JsonError::new(kind, expected, None, self.span(start))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scanner::Scanner;
+    use crate::tokens::JsonToken;
+    use crate::JsonErrorKind;
+
+    fn kinds(content: &str) -> Vec<JsonToken> {
+        Scanner::new(content)
+            .scan()
+            .unwrap()
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    fn error(content: &str) -> JsonErrorKind {
+        Scanner::new(content).scan().unwrap_err().kind
+    }
+
+    #[test]
+    fn scan_tokens() {
+        let tokens = Scanner::new("{\"name\": [12, -1.5e3, true, null]}")
+            .scan()
+            .unwrap();
+        let kinds: Vec<JsonToken> = tokens.iter().map(|token| token.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                JsonToken::LeftCurlyBrace,
+                JsonToken::StringVal("name".to_owned()),
+                JsonToken::Colon,
+                JsonToken::LeftSquareBrace,
+                JsonToken::IntVal(12),
+                JsonToken::Comma,
+                JsonToken::FloatVal(-1500.0),
+                JsonToken::Comma,
+                JsonToken::BoolVal(true),
+                JsonToken::Comma,
+                JsonToken::NullVal,
+                JsonToken::RightSquareBrace,
+                JsonToken::RightCurlyBrace,
+                JsonToken::EOF,
+            ]
+        );
+        let span = tokens[6].span;
+        assert_eq!(
+            (span.start, span.end, span.line, span.column),
+            (14, 20, 1, 15)
+        );
+    }
+
+    #[test]
+    fn scan_strings() {
+        assert_eq!(
+            kinds(r#""a\"b\\c\/\n\t\u00e8\ud83d\ude00""#)[0],
+            JsonToken::StringVal("a\"b\\c/\n\tè😀".to_owned())
+        );
+        assert_eq!(error("\"Mark"), JsonErrorKind::UnterminatedString);
+        assert_eq!(error("\"a\\q\""), JsonErrorKind::InvalidEscape('q'));
+        assert_eq!(error("\"\\ud83d\""), JsonErrorKind::InvalidUnicode);
+        assert_eq!(error("\"\\u12\""), JsonErrorKind::InvalidUnicode);
+        assert_eq!(error("\"a\nb\""), JsonErrorKind::ControlChar('\n'));
+    }
+
+    #[test]
+    fn scan_numbers() {
+        assert_eq!(kinds("0")[0], JsonToken::IntVal(0));
+        assert_eq!(kinds("-0.5")[0], JsonToken::FloatVal(-0.5));
+        assert_eq!(kinds("1E2")[0], JsonToken::FloatVal(100.0));
+        // integers out of the i64 range are floats
+        assert_eq!(
+            kinds("9223372036854775808")[0],
+            JsonToken::FloatVal(9223372036854775808.0)
+        );
+        for invalid in ["01", "-", "1.", ".5", "1e", "+1", "12px", "1e999"] {
+            let kind = Scanner::new(invalid).scan().map_err(|err| err.kind);
+            assert!(
+                matches!(
+                    kind,
+                    Err(JsonErrorKind::InvalidNumber | JsonErrorKind::UnexpectedChar(_))
+                ),
+                "{invalid} is not a valid number"
+            );
+        }
+    }
+
+    #[test]
+    fn scan_errors_position() {
+        let err = Scanner::new("{\n  \"name\": nul\n}").scan().unwrap_err();
+        assert_eq!(err.kind, JsonErrorKind::UnknownLiteral("nul".to_owned()));
+        assert_eq!((err.span.line, err.span.column), (2, 11));
+        assert_eq!(
+            err.to_string(),
+            "unknown literal `nul`, expected `true`, `false` or `null` at line 2, column 11"
+        );
+        assert_eq!(error("{'name': 1}"), JsonErrorKind::UnexpectedChar('\''));
+    }
+}
@@ -0,0 +1,67 @@
+//! Json Tokens definition
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::fmt;
+
+use monkey_yaml::span::Span;
+
+/// Enumerator to define the Json language, RFC 8259
+#[derive(Debug, PartialEq, Clone)]
+pub enum JsonToken {
+    /// String with the escape sequences decoded
+    StringVal(String),
+    /// Number without fraction and exponent that fits an i64
+    IntVal(i64),
+    FloatVal(f64),
+    BoolVal(bool),
+    NullVal,
+    /// :, Colon to divide a key value
+    Colon,
+    /// ,, Comma token to divide the items of a collection
+    Comma,
+    LeftCurlyBrace,
+    RightCurlyBrace,
+    LeftSquareBrace,
+    RightSquareBrace,
+    /// EOF put the end of the tokens
+    EOF,
+}
+
+impl fmt::Display for JsonToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonToken::StringVal(val) => write!(f, "`{:?}`", val),
+            JsonToken::IntVal(val) => write!(f, "`{}`", val),
+            JsonToken::FloatVal(val) => write!(f, "`{}`", val),
+            JsonToken::BoolVal(val) => write!(f, "`{}`", val),
+            JsonToken::NullVal => write!(f, "`null`"),
+            JsonToken::Colon => write!(f, "`:`"),
+            JsonToken::Comma => write!(f, "`,`"),
+            JsonToken::LeftCurlyBrace => write!(f, "`{{`"),
+            JsonToken::RightCurlyBrace => write!(f, "`}}`"),
+            JsonToken::LeftSquareBrace => write!(f, "`[`"),
+            JsonToken::RightSquareBrace => write!(f, "`]`"),
+            JsonToken::EOF => write!(f, "end of file"),
+        }
+    }
+}
+
+/// Token found by the scanner, with the position
+/// in the content where it is defined.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Token {
+    pub kind: JsonToken,
+    pub span: Span,
+}
+
+impl Token {
+    pub fn new(kind: JsonToken, span: Span) -> Self {
+        Token { kind, span }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
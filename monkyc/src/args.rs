@@ -0,0 +1,156 @@
+//! Command line arguments of the monkyc compiler.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::{Parser, ValueEnum};
+use monkey_codegen::golang::tags::{TagFamily, TagNaming};
+
+/// Albert transpiler that converts a data model like
+/// YAML/JSON into source code like Go.
+#[derive(Debug, Parser)]
+#[command(name = "monkyc", version, about)]
+pub struct MonkycArgs {
+    /// Data model files to compile
+    #[arg(required = true)]
+    pub inputs: Vec<PathBuf>,
+    /// Format of the input data model
+    #[arg(long, value_enum, default_value_t = InputFormat::Yaml)]
+    pub from: InputFormat,
+    /// Target language of the generated source code
+    #[arg(long, value_enum, default_value_t = TargetLang::Go)]
+    pub to: TargetLang,
+    /// Directory where the generated sources are written
+    #[arg(short, long, default_value = ".")]
+    pub output: PathBuf,
+    /// Name of the package/module of the generated sources
+    #[arg(long, default_value = "model")]
+    pub package: String,
+    /// Struct tags emitted for each field (json, yaml), an empty
+    /// value or none to emit no tags
+    #[arg(long, default_value = "json")]
+    pub tags: TagList,
+    /// Naming convention of the struct tags (original, snake, camel, kebab)
+    #[arg(long, default_value = "original")]
+    pub tag_naming: TagNaming,
+}
+
+/// Input data model supported by the compiler front-end.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum InputFormat {
+    Yaml,
+    Json,
+    #[value(name = "jsonschema")]
+    JsonSchema,
+}
+
+impl fmt::Display for InputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputFormat::Yaml => write!(f, "yaml"),
+            InputFormat::Json => write!(f, "json"),
+            InputFormat::JsonSchema => write!(f, "jsonschema"),
+        }
+    }
+}
+
+/// Comma separated list of the struct tags, e.g: `json,yaml`,
+/// where `none` or an empty value is the empty list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagList(pub Vec<TagFamily>);
+
+impl FromStr for TagList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "" | "none" => Ok(TagList(vec![])),
+            tags => tags
+                .split(',')
+                .map(|tag| tag.trim().parse())
+                .collect::<Result<_, _>>()
+                .map(TagList),
+        }
+    }
+}
+
+/// Target language supported by the compiler back-end.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum TargetLang {
+    Go,
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use clap::Parser;
+    use monkey_codegen::golang::tags::{TagFamily, TagNaming};
+
+    use crate::args::{InputFormat, MonkycArgs, TagList, TargetLang};
+
+    #[test]
+    fn parse_default_args() {
+        let args = MonkycArgs::try_parse_from(["monkyc", "model.yml"]).unwrap();
+        assert_eq!(args.inputs, vec![PathBuf::from("model.yml")]);
+        assert_eq!(args.from, InputFormat::Yaml);
+        assert_eq!(args.to, TargetLang::Go);
+        assert_eq!(args.output, PathBuf::from("."));
+        assert_eq!(args.package, "model");
+        assert_eq!(args.tags, TagList(vec![TagFamily::Json]));
+        assert_eq!(args.tag_naming, TagNaming::Original);
+    }
+
+    #[test]
+    fn parse_all_args() {
+        let args = MonkycArgs::try_parse_from([
+            "monkyc",
+            "--from",
+            "json",
+            "--to",
+            "go",
+            "-o",
+            "out",
+            "--package",
+            "api",
+            "--tags",
+            "json,yaml",
+            "--tag-naming",
+            "snake",
+            "one.json",
+            "two.json",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.inputs,
+            vec![PathBuf::from("one.json"), PathBuf::from("two.json")]
+        );
+        assert_eq!(args.from, InputFormat::Json);
+        assert_eq!(args.output, PathBuf::from("out"));
+        assert_eq!(args.package, "api");
+        assert_eq!(args.tags, TagList(vec![TagFamily::Json, TagFamily::Yaml]));
+        assert_eq!(args.tag_naming, TagNaming::SnakeCase);
+    }
+
+    #[test]
+    fn parse_invalid_args() {
+        // the inputs are required
+        assert!(MonkycArgs::try_parse_from(["monkyc"]).is_err());
+        assert!(MonkycArgs::try_parse_from(["monkyc", "--from", "xml", "a.xml"]).is_err());
+        assert!(MonkycArgs::try_parse_from(["monkyc", "--to", "rust", "a.yml"]).is_err());
+        assert!(MonkycArgs::try_parse_from(["monkyc", "--tags", "toml", "a.yml"]).is_err());
+        assert!(MonkycArgs::try_parse_from(["monkyc", "--tags", "json,none", "a.yml"]).is_err());
+    }
+
+    #[test]
+    fn parse_no_tags() {
+        for tags in ["none", ""] {
+            let args = MonkycArgs::try_parse_from(["monkyc", "--tags", tags, "a.yml"]).unwrap();
+            assert_eq!(args.tags, TagList(vec![]));
+        }
+        let args = MonkycArgs::try_parse_from(["monkyc", "--tags=", "a.yml"]).unwrap();
+        assert_eq!(args.tags, TagList(vec![]));
+    }
+}
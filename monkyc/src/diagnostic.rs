@@ -0,0 +1,173 @@
+//! Compiler diagnostic that point to the position of the
+//! input file where the error is found.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use monkey_codegen::schema::SchemaError;
+use monkey_json::JsonError;
+use monkey_yaml::parser::YamlParserError;
+use monkey_yaml::scanner::ScannerError;
+use monkey_yaml::span::Span;
+use monkey_yaml::YamlError;
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub message: String,
+    pub path: PathBuf,
+    /// line of the error, starting from 1
+    pub line: usize,
+    /// column of the error, starting from 1
+    pub column: usize,
+    /// source line that contains the error
+    pub source_line: String,
+    /// number of chars to underline
+    pub len: usize,
+}
+
+impl Diagnostic {
+    pub fn new(
+        path: &Path,
+        content: &str,
+        message: &str,
+        line: usize,
+        column: usize,
+        len: usize,
+    ) -> Self {
+        let source_line = content
+            .lines()
+            .nth(line.saturating_sub(1))
+            .unwrap_or("")
+            .to_string();
+        // underline only the content inside the line of the error
+        let line_len = source_line
+            .chars()
+            .count()
+            .saturating_sub(column.saturating_sub(1));
+        let len = len.min(line_len);
+        Diagnostic {
+            message: message.to_string(),
+            path: path.to_path_buf(),
+            line,
+            column,
+            source_line,
+            len: len.max(1),
+        }
+    }
+
+    pub fn from_scanner(path: &Path, content: &str, err: &ScannerError) -> Self {
+        Diagnostic::new(
+            path,
+            content,
+            &err.kind.to_string(),
+            err.line,
+            err.column,
+            err.snippet.chars().count(),
+        )
+    }
+
+    pub fn from_parser(path: &Path, content: &str, err: &YamlParserError) -> Self {
+        Diagnostic::from_span(path, content, &err.message(), &err.span)
+    }
+
+    pub fn from_yaml(path: &Path, content: &str, err: &YamlError) -> Self {
+        match err {
+            YamlError::Scanner(err) => Diagnostic::from_scanner(path, content, err),
+            YamlError::Parser(err) => Diagnostic::from_parser(path, content, err),
+        }
+    }
+
+    pub fn from_json(path: &Path, content: &str, err: &JsonError) -> Self {
+        Diagnostic::from_span(path, content, &err.message(), &err.span)
+    }
+
+    /// Build the diagnostic from a schema error, and return None
+    /// if the error does not point to the content.
+    pub fn from_schema(path: &Path, content: &str, err: &SchemaError) -> Option<Self> {
+        let span = err.span?;
+        Some(Diagnostic::from_span(path, content, &err.message, &span))
+    }
+
+    fn from_span(path: &Path, content: &str, message: &str, span: &Span) -> Self {
+        let snippet = content.get(span.start..span.end).unwrap_or("");
+        Diagnostic::new(
+            path,
+            content,
+            message,
+            span.line,
+            span.column,
+            snippet.chars().count(),
+        )
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(f, "{}", self.message)?;
+        writeln!(
+            f,
+            "{gutter}--> {}:{}:{}",
+            self.path.display(),
+            self.line,
+            self.column
+        )?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(
+            f,
+            "{gutter} | {}{}",
+            " ".repeat(self.column.saturating_sub(1)),
+            "^".repeat(self.len)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use monkey_yaml::span::Span;
+    use monkey_yaml::ParseOptions;
+
+    use crate::diagnostic::Diagnostic;
+
+    #[test]
+    fn render_diagnostic() {
+        let content = "User:\n  name: {type: string]\n";
+        let err = monkey_yaml::parse_str(content, ParseOptions::default()).unwrap_err();
+        let diagnostic = Diagnostic::from_yaml(Path::new("model.yml"), content, &err);
+        let expected = [
+            "unexpected `]`, expected `,` or `}`",
+            " --> model.yml:2:22",
+            "  |",
+            "2 |   name: {type: string]",
+            "  |                      ^",
+        ];
+        assert_eq!(diagnostic.to_string(), expected.join("\n"));
+
+        let content = "{\"User\": [\"name\", tru]}";
+        let err = monkey_json::parse_str(content).unwrap_err();
+        let diagnostic = Diagnostic::from_json(Path::new("model.json"), content, &err);
+        let expected = [
+            "unknown literal `tru`, expected `true`, `false` or `null`",
+            " --> model.json:1:19",
+            "  |",
+            "1 | {\"User\": [\"name\", tru]}",
+            "  |                   ^^^",
+        ];
+        assert_eq!(diagnostic.to_string(), expected.join("\n"));
+    }
+
+    #[test]
+    fn diagnostic_default_span() {
+        // the span of the events without a position, e.g: StreamStart
+        let span = Span::default();
+        let diagnostic = Diagnostic::from_span(Path::new("a.yml"), "a: 1", "error", &span);
+        assert_eq!(
+            (diagnostic.line, diagnostic.column, diagnostic.len),
+            (0, 0, 1)
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Compiler driver that run the pipeline
+//! scanner -> parser -> code generator over
+//! each input file.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use monkey_codegen::golang::codegen::Golang;
+use monkey_codegen::golang::tags::GoOptions;
+use monkey_codegen::schema::SchemaError;
+use monkey_codegen::{CodeGenStrategy, DataModel};
+use monkey_yaml::ParseOptions;
+
+use crate::args::{InputFormat, MonkycArgs, TargetLang};
+use crate::diagnostic::Diagnostic;
+use crate::error::CompilerError;
+
+pub struct Driver<'a> {
+    args: &'a MonkycArgs,
+    strategy: Box<dyn for<'b> CodeGenStrategy<'b>>,
+}
+
+impl<'a> Driver<'a> {
+    pub fn new(args: &'a MonkycArgs) -> Self {
+        let strategy = match args.to {
+            TargetLang::Go => {
+                let options = GoOptions {
+                    tags: args.tags.0.clone(),
+                    naming: args.tag_naming,
+                };
+                Box::new(Golang::new_with_options(&args.package, options))
+            }
+        };
+        Driver { args, strategy }
+    }
+
+    /// Compile all the input files, and stop at the first error.
+    pub fn run(&self) -> Result<Vec<PathBuf>, CompilerError> {
+        fs::create_dir_all(&self.args.output).map_err(|err| CompilerError::Io {
+            path: self.args.output.clone(),
+            source: err,
+        })?;
+        self.args
+            .inputs
+            .iter()
+            .map(|input| self.compile(input))
+            .collect()
+    }
+
+    /// Compile a single file and return the path of the
+    /// generated source.
+    fn compile(&self, input: &Path) -> Result<PathBuf, CompilerError> {
+        let content = fs::read_to_string(input).map_err(|err| CompilerError::Io {
+            path: input.to_path_buf(),
+            source: err,
+        })?;
+
+        let mut source = vec![];
+        match self.args.from {
+            InputFormat::Yaml => {
+                let ir =
+                    monkey_yaml::parse_str(&content, ParseOptions::default()).map_err(|err| {
+                        CompilerError::Diagnostic(Box::new(Diagnostic::from_yaml(
+                            input, &content, &err,
+                        )))
+                    })?;
+                let model = DataModel::Yaml(&ir);
+                self.strategy
+                    .generate(&model, &mut source)
+                    .map_err(|err| codegen_error(input, &content, err))?;
+            }
+            InputFormat::Json => {
+                let ir = monkey_json::parse_str(&content).map_err(|err| {
+                    CompilerError::Diagnostic(Box::new(Diagnostic::from_json(
+                        input, &content, &err,
+                    )))
+                })?;
+                let model = DataModel::Json(&ir);
+                self.strategy
+                    .generate(&model, &mut source)
+                    .map_err(|err| codegen_error(input, &content, err))?;
+            }
+            format => return Err(CompilerError::UnsupportedFormat(format)),
+        }
+
+        let out_path = self.output_path(input);
+        fs::write(&out_path, source).map_err(|err| CompilerError::Io {
+            path: out_path.clone(),
+            source: err,
+        })?;
+        Ok(out_path)
+    }
+
+    /// Build the path of the generated source from the input
+    /// file name, e.g: `model_one.yml` -> `<output>/model_one.go`.
+    fn output_path(&self, input: &Path) -> PathBuf {
+        let stem = input.file_stem().unwrap_or(input.as_os_str());
+        self.args
+            .output
+            .join(stem)
+            .with_extension(self.strategy.extension())
+    }
+}
+
+/// Map the code generator error in a diagnostic when the
+/// error point to a node of the model.
+fn codegen_error(input: &Path, content: &str, err: io::Error) -> CompilerError {
+    let diagnostic = err
+        .get_ref()
+        .and_then(|source| source.downcast_ref::<SchemaError>())
+        .and_then(|source| Diagnostic::from_schema(input, content, source));
+    match diagnostic {
+        Some(diagnostic) => CompilerError::Diagnostic(Box::new(diagnostic)),
+        None => CompilerError::CodeGen {
+            path: input.to_path_buf(),
+            source: err,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::Path;
+
+    use clap::Parser;
+
+    use crate::args::{InputFormat, MonkycArgs};
+    use crate::driver::Driver;
+    use crate::error::CompilerError;
+
+    const MODEL_ONE_GO: &str = include_str!("../../codegen/tests/golden/model_one.go");
+
+    /// Run the driver with the arguments given in input, where
+    /// the output directory is inside the directory given in input.
+    fn run(dir: &Path, args: &[&str]) -> Result<Vec<std::path::PathBuf>, CompilerError> {
+        let output = dir.join("out");
+        let mut argv = vec!["monkyc", "-o", output.to_str().unwrap()];
+        argv.extend(args);
+        let args = MonkycArgs::try_parse_from(argv).unwrap();
+        Driver::new(&args).run()
+    }
+
+    #[test]
+    fn run_yaml_and_json_models() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = dir.path().join("model_one.yml");
+        let json = dir.path().join("model_two.json");
+        fs::write(&yaml, include_str!("../../examples/model_one.yml")).unwrap();
+        fs::write(&json, include_str!("../../examples/model_one.json")).unwrap();
+
+        let outputs = run(dir.path(), &["--from", "yaml", yaml.to_str().unwrap()]).unwrap();
+        assert_eq!(outputs, vec![dir.path().join("out").join("model_one.go")]);
+        assert_eq!(fs::read_to_string(&outputs[0]).unwrap(), MODEL_ONE_GO);
+
+        let outputs = run(dir.path(), &["--from", "json", json.to_str().unwrap()]).unwrap();
+        assert_eq!(outputs, vec![dir.path().join("out").join("model_two.go")]);
+        assert_eq!(fs::read_to_string(&outputs[0]).unwrap(), MODEL_ONE_GO);
+    }
+
+    #[test]
+    fn run_with_package_and_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("user.yml");
+        fs::write(&input, "User:\n  - user_name: {type: string}\n").unwrap();
+        let args = [
+            "--package",
+            "api",
+            "--tags",
+            "json,yaml",
+            "--tag-naming",
+            "camel",
+            input.to_str().unwrap(),
+        ];
+        let outputs = run(dir.path(), &args).unwrap();
+        let source = fs::read_to_string(&outputs[0]).unwrap();
+        assert!(source.contains("package api\n"));
+        assert!(source.contains("UserName string `json:\"userName\" yaml:\"userName\"`"));
+    }
+
+    #[test]
+    fn run_without_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("user.yml");
+        fs::write(&input, "User:\n  - user_name: {type: string}\n").unwrap();
+        let outputs = run(dir.path(), &["--tags", "none", input.to_str().unwrap()]).unwrap();
+        let source = fs::read_to_string(&outputs[0]).unwrap();
+        assert!(source.contains("\tUserName string\n"));
+    }
+
+    #[test]
+    fn run_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("model.yml");
+        fs::write(&input, "User:\n  name: [a\n").unwrap();
+
+        let err = run(dir.path(), &[input.to_str().unwrap()]).unwrap_err();
+        let CompilerError::Diagnostic(diagnostic) = err else {
+            panic!("expected a diagnostic, found {:?}", err);
+        };
+        assert_eq!(diagnostic.path, input);
+
+        let err = run(
+            dir.path(),
+            &["--from", "jsonschema", input.to_str().unwrap()],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            CompilerError::UnsupportedFormat(InputFormat::JsonSchema)
+        ));
+
+        let missing = dir.path().join("missing.yml");
+        let err = run(dir.path(), &[missing.to_str().unwrap()]).unwrap_err();
+        assert!(matches!(err, CompilerError::Io { path, .. } if path == missing));
+    }
+}
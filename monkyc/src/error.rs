@@ -0,0 +1,30 @@
+//! Errors reported by the monkyc compiler driver.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::io;
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::args::InputFormat;
+use crate::diagnostic::Diagnostic;
+
+#[derive(Debug, Error)]
+pub enum CompilerError {
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("{0}")]
+    Diagnostic(Box<Diagnostic>),
+    #[error("input format `{0}` is not supported yet")]
+    UnsupportedFormat(InputFormat),
+    #[error("{}: unable to generate the source code: {source}", path.display())]
+    CodeGen {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
@@ -1,3 +1,31 @@
+//! monkyc: the Albert transpiler command line
+//! that converts a data model into source code.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::process;
+
+use clap::Parser;
+
+mod args;
+mod diagnostic;
+mod driver;
+mod error;
+
+use args::MonkycArgs;
+use driver::Driver;
+
 fn main() {
-    println!("Hello, world!");
+    let args = MonkycArgs::parse();
+    let driver = Driver::new(&args);
+    match driver.run() {
+        Ok(outputs) => {
+            for out in outputs {
+                println!("generated {}", out.display());
+            }
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    }
 }
@@ -22,11 +22,11 @@ impl<T> Stream<T> for BasicStream<T>
 where
     T: fmt::Display + Clone,
 {
-    fn new(tokens: &Vec<T>) -> Self {
+    fn new(tokens: &[T]) -> Self {
         BasicStream::new_with_pos(tokens, 0)
     }
 
-    fn new_with_pos(stream: &Vec<T>, pos: usize) -> Self {
+    fn new_with_pos(stream: &[T], pos: usize) -> Self {
         BasicStream {
             pos,
             stream: stream.to_vec(),
@@ -36,15 +36,15 @@ where
 
     /// advance the position and return the previous element
     /// in position - 1
-    fn advance<'c>(&'c mut self) -> &'c T {
+    fn advance(&mut self) -> &T {
         self.next();
         if self.is_end() {
-            return &self.stream.last().unwrap();
+            return self.stream.last().unwrap();
         }
         self.prev()
     }
 
-    fn lookup<'c>(&'c self, step: usize) -> Option<&'c T> {
+    fn lookup(&self, step: usize) -> Option<&T> {
         if self.size > self.pos + step {
             return None;
         }
@@ -55,13 +55,13 @@ where
         self.pos += 1;
     }
 
-    fn prev<'c>(&'c self) -> &'c T {
+    fn prev(&self) -> &T {
         assert!(self.pos < self.size, "prev: out of bound");
         &self.stream[self.pos - 1]
     }
 
     /// return he token at the current position
-    fn peek<'c>(&'c self) -> &'c T {
+    fn peek(&self) -> &T {
         assert!(self.pos < self.size);
         &self.stream[self.pos]
     }
@@ -80,22 +80,22 @@ where
 }
 
 pub trait Stream<T> {
-    fn new(tokens: &Vec<T>) -> Self;
+    fn new(tokens: &[T]) -> Self;
 
-    fn new_with_pos(stream: &Vec<T>, pos: usize) -> Self;
+    fn new_with_pos(stream: &[T], pos: usize) -> Self;
 
     /// advance the position and return the previous element
     /// in position - 1
-    fn advance<'c>(&'c mut self) -> &'c T;
+    fn advance(&mut self) -> &T;
 
-    fn lookup<'c>(&'c self, step: usize) -> Option<&'c T>;
+    fn lookup(&self, step: usize) -> Option<&T>;
 
     fn next(&mut self);
 
-    fn prev<'c>(&'c self) -> &'c T;
+    fn prev(&self) -> &T;
 
     /// return he token at the current position
-    fn peek<'c>(&'c self) -> &'c T;
+    fn peek(&self) -> &T;
 
     fn match_tok(&self, tok: &str) -> bool;
 
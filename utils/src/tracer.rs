@@ -15,22 +15,21 @@ pub trait Tracer {
         Self: Sized;
 
     /// Generic log call to log a message in a specified level.
-    fn log(&self, level: TraceLevel, msg: &String) {
+    fn log(&self, level: TraceLevel, msg: &str) {
         match level {
             TraceLevel::Debug => self.debug(msg),
             TraceLevel::Info => self.info(msg),
         }
     }
 
-    fn debug(&self, msg: &String);
+    fn debug(&self, msg: &str);
 
-    fn info(&self, msg: &String);
+    fn info(&self, msg: &str);
 }
 
 impl fmt::Debug for dyn Tracer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "tracer");
-        Ok(())
+        write!(f, "tracer")
     }
 }
 
@@ -45,11 +44,11 @@ impl Tracer for DefLogTracer {
         DefLogTracer {}
     }
 
-    fn debug(&self, msg: &String) {
+    fn debug(&self, msg: &str) {
         debug!("{}", msg);
     }
 
-    fn info(&self, msg: &String) {
+    fn info(&self, msg: &str) {
         info!("{}", msg);
     }
 }
@@ -64,7 +63,7 @@ impl Tracer for DummyTracer {
         DummyTracer {}
     }
 
-    fn debug(&self, _msg: &String) {}
+    fn debug(&self, _msg: &str) {}
 
-    fn info(&self, _msg: &String) {}
+    fn info(&self, _msg: &str) {}
 }
@@ -0,0 +1,85 @@
+//! Benchmark of the YAML scanner and parser over models of growing size,
+//! multi-line plain scalars inside flow collections and long runs of
+//! comments, the throughput must stay the same when the size grows.
+//!
+//! author: https://github.com/vincenzopalazzo
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use monkey_yaml::scanner::scanner::Scanner;
+use monkey_yaml::scanner::YamlScanner;
+use monkey_yaml::ParseOptions;
+
+/// Build a data model with the number of models given in input.
+fn model(models: usize) -> String {
+    let mut content = String::from("# Models générés pour le benchmark\n");
+    for model in 0..models {
+        content.push_str(&format!(
+            "Model{model}:\n  # Nom de l'utilisateur\n  name: {{type: string, doc_comment: \"Nom complet\"}}\n  score:\n    type: float\n    nullable: true\n  tags:\n    - doc_comment: >-\n        Étiquettes de\n        l'utilisateur\n    - type: \"[]string\"\n"
+        ));
+    }
+    content
+}
+
+/// Build a flow sequence and a flow mapping with the number of
+/// items given in input, where each plain scalar is on more lines.
+fn flow_scalars(items: usize) -> String {
+    let mut content = String::from("tags: [\n");
+    for item in 0..items {
+        content.push_str(&format!("  first {item}\n  second line,\n"));
+    }
+    content.push_str("]\nnames: {\n");
+    for item in 0..items {
+        content.push_str(&format!("  key{item}: first\n    second line,\n"));
+    }
+    content.push_str("}\n");
+    content
+}
+
+/// Build a mapping where the entries are separated by a
+/// run of comments with the number of lines given in input.
+fn comment_runs(lines: usize) -> String {
+    let run = "  # c\n".repeat(lines);
+    format!("a:\n{run}  b: 1\nc:\n{run}d: 2\n")
+}
+
+fn scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scanner");
+    for models in [100, 1_000, 10_000] {
+        let content = model(models);
+        group.throughput(Throughput::Bytes(content.len() as u64));
+        group.bench_with_input(BenchmarkId::new("scan", models), &content, |b, content| {
+            b.iter(|| Scanner::new().scan(black_box(content)).unwrap())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("tokens", models),
+            &content,
+            |b, content| b.iter(|| Scanner::new().tokens(black_box(content)).count()),
+        );
+        group.bench_with_input(BenchmarkId::new("parse", models), &content, |b, content| {
+            b.iter(|| monkey_yaml::parse_str(black_box(content), ParseOptions::default()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parser");
+    for size in [100, 1_000, 10_000] {
+        let inputs = [
+            ("flow_scalars", flow_scalars(size)),
+            ("comment_runs", comment_runs(size)),
+        ];
+        for (name, content) in inputs {
+            group.throughput(Throughput::Bytes(content.len() as u64));
+            group.bench_with_input(BenchmarkId::new(name, size), &content, |b, content| {
+                b.iter(|| {
+                    monkey_yaml::parse_str(black_box(content), ParseOptions::default()).unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scan, parse);
+criterion_main!(benches);
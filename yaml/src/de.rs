@@ -0,0 +1,435 @@
+//! Serde Deserializer implementation over the YAML tree
+//! produced by the parser, available with the `serde` feature.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::error;
+use std::fmt;
+use std::fmt::Formatter;
+
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::parser::options::ParseOptions;
+use crate::parser::tokens::{MappingEntry, YamlNode, YamlObject};
+use crate::parser::YamlParserError;
+use crate::scanner::ScannerError;
+use crate::span::Span;
+use crate::YamlError;
+
+/// Deserialization error with the position of the YAML
+/// node where the error is found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub message: String,
+    /// position of the node, if known
+    pub span: Option<Span>,
+}
+
+impl Error {
+    pub fn new(message: &str, span: Option<Span>) -> Self {
+        Error {
+            message: message.to_string(),
+            span,
+        }
+    }
+
+    /// Attach the position of the node to the error, if it
+    /// does not have one yet.
+    fn at(mut self, span: Span) -> Self {
+        self.span.get_or_insert(span);
+        self
+    }
+}
+
+impl error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{} at {}", self.message, span),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::new(&msg.to_string(), None)
+    }
+}
+
+impl From<ScannerError> for Error {
+    fn from(err: ScannerError) -> Self {
+        let span = Span::new(err.offset, err.offset, err.line, err.column);
+        Error::new(&err.kind.to_string(), Some(span))
+    }
+}
+
+impl From<YamlParserError> for Error {
+    fn from(err: YamlParserError) -> Self {
+        Error::new(&err.message(), Some(err.span))
+    }
+}
+
+impl From<YamlError> for Error {
+    fn from(err: YamlError) -> Self {
+        match err {
+            YamlError::Scanner(err) => err.into(),
+            YamlError::Parser(err) => err.into(),
+        }
+    }
+}
+
+/// Deserialize an instance of `T` from the YAML content,
+/// that must contains at most one document.
+pub fn from_str<T: DeserializeOwned>(content: &str) -> Result<T, Error> {
+    from_str_with_options(content, ParseOptions::default())
+}
+
+/// Deserialize an instance of `T` from the YAML content parsed
+/// with the options given in input, e.g: to limit the size of
+/// an untrusted content.
+pub fn from_str_with_options<T: DeserializeOwned>(
+    content: &str,
+    options: ParseOptions,
+) -> Result<T, Error> {
+    let documents = crate::parse_str(content, options)?;
+    match documents.as_slice() {
+        [] => T::deserialize(Deserializer::new(&YamlNode::new(
+            YamlObject::Null,
+            Span::default(),
+        ))),
+        [document] => from_node(document),
+        [_, second, ..] => Err(Error::new(
+            &format!("expected a single document, found {}", documents.len()),
+            Some(second.span),
+        )),
+    }
+}
+
+/// Deserialize an instance of `T` from a node of the YAML tree,
+/// the strings of `T` can borrow from the tree.
+pub fn from_node<'de, T: de::Deserialize<'de>>(node: &'de YamlNode) -> Result<T, Error> {
+    T::deserialize(Deserializer::new(node))
+}
+
+/// Serde Deserializer over a node of the YAML tree.
+pub struct Deserializer<'de> {
+    node: &'de YamlNode,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(node: &'de YamlNode) -> Self {
+        Deserializer { node }
+    }
+
+    /// Node that contains the value, ignoring the references,
+    /// the tags and the comments of the document.
+    fn value(&self) -> &'de YamlNode {
+        let node = self.node.resolve();
+        match &node.object {
+            YamlObject::Document(nodes) => nodes
+                .iter()
+                .find(|node| !matches!(node.object, YamlObject::Comment(_)))
+                .map(|node| Deserializer::new(node).value())
+                .unwrap_or(node),
+            _ => node,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let node = self.value();
+        let value = match &node.object {
+            YamlObject::Str(content) => visitor.visit_borrowed_str(content),
+            YamlObject::Int(value) => visitor.visit_i64(*value),
+            YamlObject::Float(value) => visitor.visit_f64(*value),
+            YamlObject::Bool(value) => visitor.visit_bool(*value),
+            YamlObject::List(items) => visitor.visit_seq(SeqAccess {
+                items: items.iter(),
+            }),
+            YamlObject::Mapping(entries, _) => visitor.visit_map(MapAccess {
+                entries: entries.iter(),
+                value: None,
+            }),
+            _ => visitor.visit_unit(),
+        };
+        value.map_err(|err| err.at(node.span))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value().object {
+            YamlObject::Null | YamlObject::Document(_) => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let node = self.value();
+        let value = match &node.object {
+            YamlObject::Str(variant) => visitor.visit_enum(variant.as_str().into_deserializer()),
+            YamlObject::Mapping(entries, _) if entries.len() == 1 => {
+                visitor.visit_enum(EnumAccess { entry: &entries[0] })
+            }
+            _ => Err(Error::new(
+                "invalid enum, expected a string or a mapping with a single key",
+                None,
+            )),
+        };
+        value.map_err(|err| err.at(node.span))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+/// wrapping the way to visit the items of a YAML list.
+struct SeqAccess<'de> {
+    items: std::slice::Iter<'de, YamlNode>,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        self.items
+            .next()
+            .map(|item| seed.deserialize(Deserializer::new(item)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+/// wrapping the way to visit the entries of a YAML mapping.
+struct MapAccess<'de> {
+    entries: std::slice::Iter<'de, MappingEntry>,
+    value: Option<&'de YamlNode>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.entries.next() {
+            Some(entry) => {
+                self.value = Some(&entry.value);
+                seed.deserialize(Deserializer::new(&entry.key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(Deserializer::new(value)),
+            None => Err(de::Error::custom("value requested before the key")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// wrapping the way to visit an enum variant defined as
+/// a mapping with a single entry, e.g: `Circle: { radius: 2 }`.
+struct EnumAccess<'de> {
+    entry: &'de MappingEntry,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'de> {
+    type Error = Error;
+    type Variant = Deserializer<'de>;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Error> {
+        let variant = seed.deserialize(Deserializer::new(&self.entry.key))?;
+        Ok((variant, Deserializer::new(&self.entry.value)))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use indoc::indoc;
+    use serde::Deserialize;
+
+    use crate::de::{from_str, from_str_with_options};
+    use crate::{DuplicateKeys, ParseOptions};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Shape {
+        Point,
+        Circle { radius: f64 },
+        Square(u32),
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        name: String,
+        port: u16,
+        debug: bool,
+        ratio: f32,
+        tags: Vec<String>,
+        owner: Option<String>,
+        limits: HashMap<String, i64>,
+        shapes: Vec<Shape>,
+    }
+
+    #[test]
+    fn deserialize_struct() {
+        let content = indoc! {"
+        name: server
+        port: 8080
+        debug: true
+        ratio: 1
+        tags: [a, b]
+        owner: null
+        limits:
+          cpu: 2
+        shapes:
+          - Point
+          - Circle:
+              radius: 1.5
+          - Square: 3
+        "};
+        let config: Config = from_str(content).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                name: "server".to_string(),
+                port: 8080,
+                debug: true,
+                ratio: 1.0,
+                tags: vec!["a".to_string(), "b".to_string()],
+                owner: None,
+                limits: HashMap::from([("cpu".to_string(), 2)]),
+                shapes: vec![
+                    Shape::Point,
+                    Shape::Circle { radius: 1.5 },
+                    Shape::Square(3)
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_aliases_and_tags() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Audit {
+            created: String,
+        }
+
+        let content = indoc! {"
+        base: &audit
+          created: today
+        copy: *audit
+        tagged: !custom
+          created: tomorrow
+        "};
+        let audits: HashMap<String, Audit> = from_str(content).unwrap();
+        assert_eq!(audits["copy"], audits["base"]);
+        assert_eq!(audits["tagged"].created, "tomorrow");
+    }
+
+    #[test]
+    fn deserialize_errors_with_line() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Server {
+            host: String,
+            port: u16,
+        }
+
+        let content = indoc! {"
+        host: localhost
+        port: -1
+        "};
+        let err = from_str::<Server>(content).unwrap_err();
+        assert_eq!(err.span.unwrap().line, 2);
+        assert!(err.to_string().ends_with("at line 2, column 7"), "{}", err);
+
+        let err = from_str::<Server>("host: localhost\n").unwrap_err();
+        assert_eq!(err.to_string(), "missing field `port` at line 1, column 1");
+
+        let err = from_str::<Server>("host: [\n").unwrap_err();
+        assert_eq!(err.span.unwrap().line, 2);
+
+        let err = from_str::<Server>("host: a\n---\nhost: b\n").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("expected a single document, found 2"));
+    }
+
+    #[test]
+    fn deserialize_with_options() {
+        let content = "name: a\nname: b\n";
+        let err = from_str::<HashMap<String, String>>(content).unwrap_err();
+        assert_eq!(err.span.unwrap().line, 2);
+        let options = ParseOptions::new().duplicate_keys(DuplicateKeys::KeepLast);
+        let map: HashMap<String, String> = from_str_with_options(content, options).unwrap();
+        assert_eq!(map["name"], "b");
+
+        let options = ParseOptions::new().max_input_size(4);
+        let err = from_str_with_options::<HashMap<String, String>>(content, options).unwrap_err();
+        assert!(err.to_string().contains("at most 4 bytes"), "{}", err);
+    }
+}
@@ -0,0 +1,621 @@
+//! emitter crate to implement the core implementation
+//! of the Yaml emitter, that write a YAML tree back to text.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::slice;
+
+use super::{EmitterError, EmitterErrorKind, YamlEmitter};
+use crate::parser::parser::YAML_TAG_PREFIX;
+use crate::parser::tokens::{MappingEntry, YamlNode, YamlObject};
+use crate::scanner::scanner::resolve_plain_scalar;
+use crate::scanner::tokens::YamlToken;
+
+/// Style used to write the collections.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CollectionStyle {
+    /// One entry for each line, e.g: `- a`
+    #[default]
+    Block,
+    /// Entries between braces, e.g: `[a, b]`
+    Flow,
+    /// Block style, except for the mappings that are defined
+    /// between braces in the parsed content.
+    Preserve,
+}
+
+/// Options to configure the content written by the emitter.
+#[derive(Debug, Clone)]
+pub struct EmitterOptions {
+    /// spaces for each level of the block collections
+    pub indent: usize,
+    /// style of the collections
+    pub style: CollectionStyle,
+}
+
+impl Default for EmitterOptions {
+    fn default() -> Self {
+        EmitterOptions {
+            indent: 2,
+            style: CollectionStyle::default(),
+        }
+    }
+}
+
+/// Layout of a node that we are writing.
+enum Body<'a> {
+    /// alias to a node already written, e.g: `*audit`
+    Alias(&'a str),
+    /// block mapping with at least one entry
+    Mapping(&'a [MappingEntry]),
+    /// block sequence with at least one item
+    Sequence(&'a [YamlNode]),
+    /// node written on a single line, a scalar or
+    /// a flow collection
+    Inline(&'a YamlNode),
+}
+
+#[derive(Debug, Default)]
+pub struct Emitter {
+    options: EmitterOptions,
+    /// nodes with an anchor already written in the document
+    /// that we are emitting, the next ones are written as alias
+    anchors: HashMap<String, Rc<YamlNode>>,
+    /// the document that we are emitting must start with `---`
+    start_pending: bool,
+    /// the `---` of the document that we are emitting is written
+    started: bool,
+    /// content written so far
+    out: String,
+}
+
+impl Emitter {
+    pub fn new() -> Self {
+        Emitter::default()
+    }
+
+    pub fn new_with_options(options: EmitterOptions) -> Self {
+        Emitter {
+            options,
+            ..Emitter::default()
+        }
+    }
+
+    /// Write the document, that starts with `---` when it is not
+    /// the first one of the stream or when it does not have a root.
+    fn emit_document(&mut self, document: &YamlNode, first: bool) -> Result<(), EmitterError> {
+        self.anchors.clear();
+        let nodes = match &document.object {
+            YamlObject::Document(nodes) => nodes.as_slice(),
+            _ => slice::from_ref(document),
+        };
+        let has_root = nodes.iter().any(|node| !is_comment(node));
+        self.start_pending = !first || !has_root;
+        self.started = false;
+        for node in nodes {
+            match &node.object {
+                YamlObject::Comment(content) => {
+                    self.start_document("");
+                    self.emit_comment(content, 0);
+                }
+                _ => self.emit_root(node)?,
+            }
+        }
+        self.start_document("");
+        Ok(())
+    }
+
+    /// Write the `---` of the document, if it is needed and
+    /// it is not written yet.
+    fn start_document(&mut self, props: &str) {
+        if self.start_pending || (!props.is_empty() && !self.started) {
+            self.out.push_str(format!("--- {props}").trim_end());
+            self.out.push('\n');
+            self.start_pending = false;
+            self.started = true;
+        }
+    }
+
+    /// Write the root node of the document.
+    fn emit_root(&mut self, node: &YamlNode) -> Result<(), EmitterError> {
+        let mut props = vec![];
+        match self.layout(node, &mut props, false) {
+            Body::Alias(name) => {
+                self.start_document("");
+                self.out.push_str(&format!("*{name}\n"));
+            }
+            Body::Inline(node) => {
+                self.start_document("");
+                props.push(self.inline(node)?);
+                self.out.push_str(&format!("{}\n", props.join(" ")));
+            }
+            body => {
+                let props = props.join(" ");
+                if self.started && !props.is_empty() {
+                    // the `---` is already written before the comments
+                    self.out.push_str(&format!("{props}\n"));
+                }
+                self.start_document(&props);
+                self.emit_block(body, 0, false)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the node after the `key:` of a mapping entry or the
+    /// `-` of a sequence item, where the block collections are
+    /// indented at `indent` spaces.
+    ///
+    /// When `compact` is true, a block collection without properties
+    /// starts on the same line of the `-`, e.g: `- name: Alice`.
+    ///
+    /// The trailing comment is written at the end of the line.
+    fn emit_value(
+        &mut self,
+        node: &YamlNode,
+        indent: usize,
+        compact: bool,
+        trailing: Option<&str>,
+    ) -> Result<(), EmitterError> {
+        let trailing = trailing
+            .map(|comment| format!(" #{comment}"))
+            .unwrap_or_default();
+        let mut props = vec![];
+        match self.layout(node, &mut props, false) {
+            Body::Alias(name) => self.out.push_str(&format!(" *{name}{trailing}\n")),
+            Body::Inline(node) => {
+                props.push(self.inline(node)?);
+                self.out
+                    .push_str(&format!(" {}{trailing}\n", props.join(" ")));
+            }
+            body => {
+                // the first line can not contain the comments
+                let has_comments = match &body {
+                    Body::Mapping(entries) => !entries[0].comments.leading.is_empty(),
+                    Body::Sequence(items) => {
+                        is_comment(&items[0]) || !items[0].comments.leading.is_empty()
+                    }
+                    Body::Alias(_) | Body::Inline(_) => false,
+                };
+                let compact = compact && props.is_empty() && trailing.is_empty() && !has_comments;
+                if compact {
+                    self.out.push_str(&" ".repeat(self.options.indent - 1));
+                } else {
+                    for prop in props {
+                        self.out.push_str(&format!(" {prop}"));
+                    }
+                    self.out.push_str(&format!("{trailing}\n"));
+                }
+                self.emit_block(body, indent, compact)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the entries of a block collection, one for each line,
+    /// where the first line is already indented when `compact`
+    /// is true.
+    fn emit_block(&mut self, body: Body, indent: usize, compact: bool) -> Result<(), EmitterError> {
+        let step = self.options.indent;
+        match body {
+            Body::Mapping(entries) => {
+                for (pos, entry) in entries.iter().enumerate() {
+                    for comment in &entry.comments.leading {
+                        self.emit_comment(comment, indent);
+                    }
+                    if pos > 0 || !compact {
+                        self.out.push_str(&" ".repeat(indent));
+                    }
+                    let key = self.key(&entry.key)?;
+                    self.out.push_str(&format!("{key}:"));
+                    let trailing = entry.comments.trailing.as_deref();
+                    self.emit_value(&entry.value, indent + step, false, trailing)?;
+                }
+            }
+            Body::Sequence(items) => {
+                for (pos, item) in items.iter().enumerate() {
+                    if let YamlObject::Comment(content) = &item.object {
+                        self.emit_comment(content, indent);
+                        continue;
+                    }
+                    for comment in &item.comments.leading {
+                        self.emit_comment(comment, indent);
+                    }
+                    if pos > 0 || !compact {
+                        self.out.push_str(&" ".repeat(indent));
+                    }
+                    self.out.push('-');
+                    let trailing = item.comments.trailing.as_deref();
+                    self.emit_value(item, indent + step, true, trailing)?;
+                }
+            }
+            Body::Alias(_) | Body::Inline(_) => unreachable!("not a block collection"),
+        }
+        Ok(())
+    }
+
+    fn emit_comment(&mut self, content: &str, indent: usize) {
+        self.out
+            .push_str(&format!("{}#{}\n", " ".repeat(indent), content));
+    }
+
+    /// Collect the anchor and the tag of the node, and return
+    /// the layout of the node that contains the value.
+    ///
+    /// The first node with an anchor define it, the next ones
+    /// are written as alias.
+    fn layout<'a>(
+        &mut self,
+        node: &'a YamlNode,
+        props: &mut Vec<String>,
+        in_flow: bool,
+    ) -> Body<'a> {
+        let is_block = |flow: bool| match self.options.style {
+            _ if in_flow => false,
+            CollectionStyle::Block => true,
+            CollectionStyle::Flow => false,
+            CollectionStyle::Preserve => !flow,
+        };
+        match &node.object {
+            YamlObject::Reference(name, target) => {
+                if let Some(anchor) = self.anchors.get(name) {
+                    if Rc::ptr_eq(anchor, target) {
+                        return Body::Alias(name);
+                    }
+                }
+                self.anchors.insert(name.to_owned(), Rc::clone(target));
+                props.push(format!("&{name}"));
+                self.layout(target, props, in_flow)
+            }
+            YamlObject::Tagged(tag, target) => {
+                props.push(tag_name(tag));
+                self.layout(target, props, in_flow)
+            }
+            YamlObject::Mapping(entries, flow) if !entries.is_empty() && is_block(*flow) => {
+                Body::Mapping(entries)
+            }
+            YamlObject::List(items) if !items.is_empty() && is_block(false) => {
+                Body::Sequence(items)
+            }
+            _ => Body::Inline(node),
+        }
+    }
+
+    /// Write the node on a single line, with the properties
+    /// in front of it.
+    fn flow(&mut self, node: &YamlNode) -> Result<String, EmitterError> {
+        let mut props = vec![];
+        let content = match self.layout(node, &mut props, true) {
+            Body::Alias(name) => return Ok(format!("*{name}")),
+            Body::Inline(node) => self.inline(node)?,
+            Body::Mapping(_) | Body::Sequence(_) => unreachable!("block collection in flow"),
+        };
+        props.push(content);
+        Ok(props.join(" "))
+    }
+
+    /// Write a scalar or a flow collection.
+    fn inline(&mut self, node: &YamlNode) -> Result<String, EmitterError> {
+        let content = match &node.object {
+            YamlObject::Mapping(entries, _) => {
+                let mut content = vec![];
+                for entry in entries {
+                    let key = self.key(&entry.key)?;
+                    let value = self.flow(&entry.value)?;
+                    content.push(format!("{key}: {value}"));
+                }
+                format!("{{{}}}", content.join(", "))
+            }
+            YamlObject::List(items) => {
+                let mut content = vec![];
+                // the comments can not be written inside a flow collection
+                for item in items.iter().filter(|item| !is_comment(item)) {
+                    content.push(self.flow(item)?);
+                }
+                format!("[{}]", content.join(", "))
+            }
+            YamlObject::Str(content) => quote(content),
+            YamlObject::Int(value) => value.to_string(),
+            YamlObject::Float(value) => match value {
+                _ if value.is_nan() => ".nan".to_string(),
+                _ if value.is_infinite() && *value > 0. => ".inf".to_string(),
+                _ if value.is_infinite() => "-.inf".to_string(),
+                _ => format!("{value:?}"),
+            },
+            YamlObject::Bool(value) => value.to_string(),
+            YamlObject::Null => "null".to_string(),
+            YamlObject::Document(_)
+            | YamlObject::Reference(..)
+            | YamlObject::Tagged(..)
+            | YamlObject::Comment(_) => {
+                return Err(EmitterError::new(
+                    EmitterErrorKind::UnsupportedNode,
+                    Some(node.span),
+                ))
+            }
+        };
+        Ok(content)
+    }
+
+    /// Write the key of a mapping entry, that must be a scalar.
+    fn key(&mut self, key: &YamlNode) -> Result<String, EmitterError> {
+        match key.resolve().object {
+            YamlObject::Str(_)
+            | YamlObject::Int(_)
+            | YamlObject::Float(_)
+            | YamlObject::Bool(_)
+            | YamlObject::Null => {}
+            _ => {
+                return Err(EmitterError::new(
+                    EmitterErrorKind::UnsupportedKey,
+                    Some(key.span),
+                ))
+            }
+        }
+        let mut content = self.flow(key)?;
+        // the `:` is part of the alias name without a space before it
+        if content.starts_with('*') {
+            content.push(' ');
+        }
+        Ok(content)
+    }
+}
+
+/// Common implementation of the Yaml Emitter
+impl YamlEmitter<YamlNode> for Emitter {
+    /// Write the documents returned by the parser, a node that is
+    /// not a `YamlObject::Document` is written as the root of
+    /// a document.
+    fn emit(&mut self, documents: &[YamlNode]) -> Result<String, EmitterError> {
+        let indent = self.options.indent;
+        if !(2..=9).contains(&indent) {
+            return Err(EmitterError::new(
+                EmitterErrorKind::InvalidIndent(indent),
+                None,
+            ));
+        }
+        self.out.clear();
+        for (pos, document) in documents.iter().enumerate() {
+            self.emit_document(document, pos == 0)?;
+        }
+        Ok(std::mem::take(&mut self.out))
+    }
+}
+
+fn is_comment(node: &YamlNode) -> bool {
+    matches!(node.object, YamlObject::Comment(_))
+}
+
+/// Write the tag in the shorthand form when it is possible,
+/// otherwise in the verbatim form, e.g: `!<tag:example.com,2000:app>`.
+fn tag_name(tag: &str) -> String {
+    if tag.starts_with('!') {
+        return tag.to_string();
+    }
+    match tag.strip_prefix(YAML_TAG_PREFIX) {
+        Some(name) => format!("!!{name}"),
+        None => format!("!<{tag}>"),
+    }
+}
+
+/// Write the string as a plain scalar when it is read back
+/// as the same string, otherwise as a double quoted string.
+fn quote(value: &str) -> String {
+    if is_plain(value) {
+        return value.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for char_at in value.chars() {
+        match char_at {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '\0' => quoted.push_str("\\0"),
+            _ if char_at.is_control() && (char_at as u32) <= 0xFF => {
+                quoted.push_str(&format!("\\x{:02X}", char_at as u32))
+            }
+            _ if char_at.is_control() => quoted.push_str(&format!("\\u{:04X}", char_at as u32)),
+            _ => quoted.push(char_at),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Check if the string can be written without quotes, so it is
+/// not resolved as another type, e.g: `true` or `12`, and it does
+/// not contain an indicator of the YAML syntax.
+fn is_plain(value: &str) -> bool {
+    let Some(first) = value.chars().next() else {
+        return false;
+    };
+    if "-?:,[]{}#&*!|>'\"%@`. ".contains(first) || value.ends_with(' ') {
+        return false;
+    }
+    if value
+        .chars()
+        .any(|char_at| ":#,[]{}".contains(char_at) || char_at.is_control())
+    {
+        return false;
+    }
+    matches!(resolve_plain_scalar(value), YamlToken::Identifier(content) if content == value)
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use crate::emitter::emitter::{CollectionStyle, Emitter, EmitterOptions};
+    use crate::emitter::{EmitterErrorKind, YamlEmitter};
+    use crate::parser::parser::Parser;
+    use crate::parser::tokens::YamlNode;
+    use crate::parser::YamlParser;
+    use crate::scanner::{scanner::Scanner, YamlScanner};
+
+    fn parse(content: &str) -> Vec<YamlNode> {
+        let tokens = Scanner::new().scan(content).unwrap();
+        Parser::new().parse(&tokens).unwrap()
+    }
+
+    fn emit_with(content: &str, indent: usize, style: CollectionStyle) -> String {
+        let options = EmitterOptions { indent, style };
+        let documents = parse(content);
+        let emitted = Emitter::new_with_options(options).emit(&documents).unwrap();
+        // the content written must be read back as the same tree
+        let emitted_again = Emitter::new_with_options(EmitterOptions { indent, style })
+            .emit(&parse(&emitted))
+            .unwrap();
+        assert_eq!(emitted, emitted_again);
+        emitted
+    }
+
+    fn emit(content: &str) -> String {
+        emit_with(content, 2, CollectionStyle::Block)
+    }
+
+    #[test]
+    fn emit_block_collections() {
+        let content = indoc! {"
+        # Users of the system
+        ModelOne: {name: string, age: int}
+        items:
+        # first item
+        - one # trailing
+        - - nested
+          - list
+        - key: value
+          other: [1, 2.5, true, null]
+        empty: []
+        "};
+        let expected = indoc! {"
+        # Users of the system
+        ModelOne:
+          name: string
+          age: int
+        items:
+          # first item
+          - one # trailing
+          - - nested
+            - list
+          - key: value
+            other:
+              - 1
+              - 2.5
+              - true
+              - null
+        empty: []
+        "};
+        assert_eq!(emit(content), expected);
+    }
+
+    #[test]
+    fn emit_with_options() {
+        let content = indoc! {"
+        model: {name: string}
+        items:
+          - a: 1
+            b: 2
+        "};
+        let expected = indoc! {"
+        model:
+            name: string
+        items:
+            -   a: 1
+                b: 2
+        "};
+        assert_eq!(emit_with(content, 4, CollectionStyle::Block), expected);
+        let expected = indoc! {"
+        {model: {name: string}, items: [{a: 1, b: 2}]}
+        "};
+        assert_eq!(emit_with(content, 2, CollectionStyle::Flow), expected);
+        let expected = indoc! {"
+        model: {name: string}
+        items:
+          - a: 1
+            b: 2
+        "};
+        assert_eq!(emit_with(content, 2, CollectionStyle::Preserve), expected);
+
+        let err = Emitter::new_with_options(EmitterOptions {
+            indent: 1,
+            ..EmitterOptions::default()
+        })
+        .emit(&parse("a: 1"))
+        .unwrap_err();
+        assert_eq!(err.kind, EmitterErrorKind::InvalidIndent(1));
+    }
+
+    #[test]
+    fn emit_quoted_strings() {
+        let content = indoc! {r#"
+        plain: hello world
+        bool: "true"
+        int: "12"
+        null: ""
+        indicator: "- item"
+        comment: "a # b"
+        key: "a: b"
+        multiline: "one\ntwo"
+        quote: "say \"hi\""
+        "#};
+        let expected = indoc! {r#"
+        plain: hello world
+        bool: "true"
+        int: "12"
+        null: ""
+        indicator: "- item"
+        comment: "a # b"
+        key: "a: b"
+        multiline: "one\ntwo"
+        quote: say "hi"
+        "#};
+        assert_eq!(emit(content), expected);
+    }
+
+    #[test]
+    fn emit_anchors_tags_and_documents() {
+        let content = indoc! {"
+        %TAG !e! tag:example.com,2000:
+        ---
+        audit: &audit
+          created: !!str 2022
+        copy: *audit
+        user: !ref User
+        app: !e!app value
+        ---
+        - &item one
+        - *item
+        ---
+        # root comment
+        !custom
+        name: root
+        --- !custom
+        name: first
+        other: second
+        ---
+        # only a comment
+        "};
+        let expected = indoc! {"
+        audit: &audit
+          created: \"2022\"
+        copy: *audit
+        user: !ref User
+        app: !<tag:example.com,2000:app> value
+        ---
+        - &item one
+        - *item
+        --- !custom
+        # root comment
+        name: root
+        --- !custom
+        name: first
+        other: second
+        ---
+        # only a comment
+        "};
+        assert_eq!(emit(content), expected);
+    }
+}
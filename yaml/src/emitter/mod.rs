@@ -0,0 +1,66 @@
+//! Main Yaml emitter crate
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::error::Error;
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::span::Span;
+
+#[allow(clippy::module_inception)]
+pub mod emitter;
+
+/// Yaml Emitter interface
+pub trait YamlEmitter<T> {
+    /// Write the documents given in input as YAML content.
+    fn emit(&mut self, documents: &[T]) -> Result<String, EmitterError>;
+}
+
+/// Kind of error that the emitter can find in the tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmitterErrorKind {
+    /// Indentation outside the range supported, from 2 to 9 spaces
+    InvalidIndent(usize),
+    /// Mapping key that is not a scalar, e.g: a list
+    UnsupportedKey,
+    /// Node that does not have a YAML representation
+    UnsupportedNode,
+}
+
+impl fmt::Display for EmitterErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EmitterErrorKind::InvalidIndent(indent) => {
+                write!(f, "invalid indentation of {indent} spaces, expected 2 to 9")
+            }
+            EmitterErrorKind::UnsupportedKey => write!(f, "unsupported key, expected a scalar"),
+            EmitterErrorKind::UnsupportedNode => write!(f, "unsupported node"),
+        }
+    }
+}
+
+/// Emitter error with the position of the node
+/// that cause the error, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmitterError {
+    pub kind: EmitterErrorKind,
+    /// position of the node inside the parsed content
+    pub span: Option<Span>,
+}
+
+impl EmitterError {
+    pub fn new(kind: EmitterErrorKind, span: Option<Span>) -> Self {
+        EmitterError { kind, span }
+    }
+}
+
+impl Error for EmitterError {}
+
+impl fmt::Display for EmitterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{} at {}", self.kind, span),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
@@ -1,5 +1,224 @@
 //! YAML format parser.
 extern crate monkey_common;
 
+use std::error::Error;
+use std::fmt;
+use std::fmt::Formatter;
+
+pub mod emitter;
 pub mod parser;
 pub mod scanner;
+pub mod span;
+
+#[cfg(feature = "serde")]
+pub mod de;
+
+#[cfg(feature = "serde")]
+pub use de::{from_node, from_str, from_str_with_options};
+pub use parser::options::{DuplicateKeys, ParseOptions};
+
+use parser::parser::Parser;
+use parser::tokens::YamlNode;
+use parser::{YamlParserError, YamlParserErrorKind};
+use scanner::scanner::Scanner;
+use scanner::tokens::YamlToken;
+use scanner::{ScannerError, YamlScanner};
+use span::Span;
+
+/// Error of the scanner or of the parser found
+/// while parsing the YAML content.
+#[derive(Debug, Clone, PartialEq)]
+pub enum YamlError {
+    Scanner(ScannerError),
+    Parser(YamlParserError),
+}
+
+impl Error for YamlError {}
+
+impl fmt::Display for YamlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            YamlError::Scanner(err) => write!(f, "{}", err),
+            YamlError::Parser(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<ScannerError> for YamlError {
+    fn from(err: ScannerError) -> Self {
+        YamlError::Scanner(err)
+    }
+}
+
+impl From<YamlParserError> for YamlError {
+    fn from(err: YamlParserError) -> Self {
+        YamlError::Parser(err)
+    }
+}
+
+/// Parse the YAML content with the options given in input, and
+/// return a `YamlObject::Document` node for each document found.
+///
+/// Each call uses a new scanner and a new parser, so the calls
+/// do not share any state, e.g:
+///
+/// let documents = monkey_yaml::parse_str("name: Mark", ParseOptions::default())?;
+///
+/// The content bigger than the max input size of the options
+/// is an error, without scanning it.
+pub fn parse_str(content: &str, options: ParseOptions) -> Result<Vec<YamlNode>, YamlError> {
+    if content.len() > options.max_input_size {
+        return Err(size_limit(content, options.max_input_size).into());
+    }
+    Parser::new_with_options(options).parse_tokens(Scanner::new().tokens(content))
+}
+
+/// Error of the content bigger than the max size, at
+/// the position of the first byte over the limit.
+fn size_limit(content: &str, max: usize) -> YamlParserError {
+    let mut end = max;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    let before = &content[..end];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before, |newline| &before[newline + 1..])
+        .chars()
+        .count()
+        + 1;
+    YamlParserError::new(
+        YamlParserErrorKind::SizeLimitExceeded,
+        Some(&format!("at most {} bytes", max)),
+        YamlToken::EOF,
+        Span::new(end, content.len(), line, column),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::tokens::YamlObject;
+    use crate::parser::YamlParserErrorKind;
+    use crate::{parse_str, DuplicateKeys, ParseOptions, YamlError};
+
+    #[test]
+    fn parse_str_documents() {
+        let documents = parse_str("name: Mark\n---\nname: Sammy\n", ParseOptions::new()).unwrap();
+        assert_eq!(documents.len(), 2);
+        let root = match &documents[1].object {
+            YamlObject::Document(nodes) => &nodes[0],
+            object => panic!("expected a document, found {:?}", object),
+        };
+        assert_eq!(
+            root.get("name").and_then(|name| name.as_str()),
+            Some("Sammy")
+        );
+
+        let err = parse_str("name: \"Mark\n", ParseOptions::new()).unwrap_err();
+        assert!(matches!(err, YamlError::Scanner(_)));
+    }
+
+    #[test]
+    fn parse_str_duplicate_keys() {
+        let content = "name: Mark\nhr: 65\nname: Sammy\n";
+        let err = parse_str(content, ParseOptions::new()).unwrap_err();
+        assert!(matches!(
+            err,
+            YamlError::Parser(ref err) if err.kind == YamlParserErrorKind::DuplicateKey
+        ));
+
+        for (policy, expected) in [
+            (DuplicateKeys::KeepFirst, "Mark"),
+            (DuplicateKeys::KeepLast, "Sammy"),
+        ] {
+            let options = ParseOptions::new().duplicate_keys(policy);
+            let documents = parse_str(content, options).unwrap();
+            let YamlObject::Document(nodes) = &documents[0].object else {
+                panic!("expected a document");
+            };
+            let entries = nodes[0].entries().unwrap();
+            assert_eq!(entries.len(), 2);
+            assert_eq!(entries[0].value.as_str(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn parse_str_max_depth() {
+        let content = "a:\n  b: [c, {d: e}]\n";
+        assert!(parse_str(content, ParseOptions::new().max_depth(4)).is_ok());
+        let err = parse_str(content, ParseOptions::new().max_depth(3)).unwrap_err();
+        let YamlError::Parser(err) = err else {
+            panic!("expected a parser error");
+        };
+        assert_eq!(err.kind, YamlParserErrorKind::DepthLimitExceeded);
+        assert_eq!((err.span.line, err.span.column), (2, 10));
+
+        // the nesting does not overflow the stack
+        let content = "[".repeat(100_000);
+        assert!(parse_str(&content, ParseOptions::new()).is_err());
+    }
+
+    #[test]
+    fn parse_str_strict() {
+        let content = "%YAML 1.3\n%FOO bar\n---\nname: Mark\n";
+        assert!(parse_str(content, ParseOptions::new()).is_ok());
+        let err = parse_str(content, ParseOptions::new().strict(true)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid directive, expected `%YAML 1.2` at line 1, column 1"
+        );
+        let content = "%FOO bar\n---\nname: Mark\n";
+        let err = parse_str(content, ParseOptions::new().strict(true)).unwrap_err();
+        assert!(err.to_string().starts_with("invalid directive"));
+    }
+
+    fn parser_error(err: YamlError) -> crate::parser::YamlParserError {
+        match err {
+            YamlError::Parser(err) => err,
+            err => panic!("expected a parser error, found {:?}", err),
+        }
+    }
+
+    #[test]
+    fn parse_str_max_input_size() {
+        let content = "name: Mark\nhr: 65\n";
+        assert!(parse_str(content, ParseOptions::new().max_input_size(content.len())).is_ok());
+        let err = parse_str(content, ParseOptions::new().max_input_size(14)).unwrap_err();
+        let err = parser_error(err);
+        assert_eq!(err.kind, YamlParserErrorKind::SizeLimitExceeded);
+        assert_eq!((err.span.line, err.span.column), (2, 4));
+    }
+
+    #[test]
+    fn parse_str_max_nodes() {
+        let content = "names: [Mark, Sammy]\n";
+        // the mapping, the key, the sequence and the two items
+        assert!(parse_str(content, ParseOptions::new().max_nodes(5)).is_ok());
+        let err = parse_str(content, ParseOptions::new().max_nodes(4)).unwrap_err();
+        let err = parser_error(err);
+        assert_eq!(err.kind, YamlParserErrorKind::NodeLimitExceeded);
+        assert_eq!((err.span.line, err.span.column), (1, 15));
+    }
+
+    #[test]
+    fn parse_str_max_alias_expansion() {
+        // billion laughs, each level expands ten times the previous one
+        let mut content = "a0: &a0 [lol, lol, lol, lol, lol, lol, lol, lol, lol, lol]\n".to_owned();
+        for level in 1..10 {
+            let aliases = vec![format!("*a{}", level - 1); 10].join(", ");
+            content.push_str(&format!("a{level}: &a{level} [{aliases}]\n"));
+        }
+        let err = parse_str(&content, ParseOptions::new()).unwrap_err();
+        let err = parser_error(err);
+        assert_eq!(err.kind, YamlParserErrorKind::AliasLimitExceeded);
+
+        let content = "base: &base {type: int64}\nid: *base\nhr: *base\n";
+        let options = ParseOptions::new().max_alias_expansion(6);
+        assert!(parse_str(content, options).is_ok());
+        let options = ParseOptions::new().max_alias_expansion(5);
+        let err = parser_error(parse_str(content, options).unwrap_err());
+        assert_eq!(err.kind, YamlParserErrorKind::AliasLimitExceeded);
+        assert_eq!((err.span.line, err.span.column), (3, 5));
+    }
+}
@@ -0,0 +1,562 @@
+//! Tree builder that consume the events of the parser,
+//! and build the tree of Yaml nodes of each document.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::rc::Rc;
+
+use super::events::{Event, EventKind, ScalarStyle};
+use super::options::DuplicateKeys;
+use super::parser::YAML_TAG_PREFIX;
+use super::tokens::{MappingEntry, YamlNode, YamlObject};
+use super::{YamlParserError, YamlParserErrorKind};
+use crate::scanner::scanner::resolve_plain_scalar;
+use crate::scanner::tokens::YamlToken;
+use crate::span::Span;
+
+/// Key that merge the entries of another mapping, e.g: `<<: *audit`
+const MERGE_KEY: &str = "<<";
+
+/// Node that is still open, and contains the
+/// nodes built from the following events.
+enum Frame {
+    Document {
+        nodes: Vec<YamlNode>,
+        start: Span,
+    },
+    Mapping {
+        /// entries of the mapping, with the flag set when
+        /// the key is the merge key `<<`
+        entries: Vec<(MappingEntry, bool)>,
+        /// key of the entry that wait for the value
+        key: Option<YamlNode>,
+        /// the key that wait for the value is the merge key
+        merge_key: bool,
+        /// comments before the entry that we are building
+        leading: Vec<String>,
+        properties: Properties,
+        flow: bool,
+        start: Span,
+    },
+    Sequence {
+        items: Vec<YamlNode>,
+        /// comments before the item that we are building
+        leading: Vec<String>,
+        properties: Properties,
+        flow: bool,
+        start: Span,
+    },
+}
+
+/// Anchor and tag of a collection, applied when it is closed.
+struct Properties {
+    anchor: Option<String>,
+    tag: Option<(String, Span)>,
+}
+
+/// Consumer of the parser events that build a
+/// `YamlObject::Document` node for each document.
+pub(crate) struct TreeBuilder {
+    documents: Vec<YamlNode>,
+    frames: Vec<Frame>,
+    /// nodes with an anchor defined in the document that
+    /// we are building, shared with the aliases to them
+    anchors: HashMap<String, Rc<YamlNode>>,
+    /// comments that annotate the next entry or item,
+    /// otherwise they are part of the document
+    comments: Vec<(String, Span)>,
+    duplicate_keys: DuplicateKeys,
+}
+
+impl TreeBuilder {
+    pub(crate) fn new(duplicate_keys: DuplicateKeys) -> Self {
+        TreeBuilder {
+            documents: vec![],
+            frames: vec![],
+            anchors: HashMap::new(),
+            comments: vec![],
+            duplicate_keys,
+        }
+    }
+
+    /// Documents built from the events consumed.
+    pub(crate) fn documents(self) -> Vec<YamlNode> {
+        self.documents
+    }
+
+    /// Consume the next event of the stream.
+    pub(crate) fn push(&mut self, event: Event) -> Result<(), YamlParserError> {
+        let span = event.span;
+        match event.kind {
+            EventKind::StreamStart | EventKind::StreamEnd => {}
+            EventKind::DocumentStart { .. } => {
+                // the anchors are defined only inside the document
+                self.anchors.clear();
+                self.frames.push(Frame::Document {
+                    nodes: vec![],
+                    start: span,
+                });
+            }
+            EventKind::DocumentEnd => {
+                self.flush_comments();
+                let Some(Frame::Document { nodes, start }) = self.frames.pop() else {
+                    unreachable!("document end without document start");
+                };
+                let document = YamlNode::new(YamlObject::Document(nodes), start.merge(&span));
+                self.documents.push(document);
+            }
+            EventKind::MappingStart { anchor, tag, flow } => {
+                self.start_node();
+                self.frames.push(Frame::Mapping {
+                    entries: vec![],
+                    key: None,
+                    merge_key: false,
+                    leading: vec![],
+                    properties: Properties { anchor, tag },
+                    flow,
+                    start: span,
+                });
+            }
+            EventKind::MappingEnd => {
+                let Some(Frame::Mapping {
+                    entries,
+                    properties,
+                    flow,
+                    start,
+                    ..
+                }) = self.frames.pop()
+                else {
+                    unreachable!("mapping end without mapping start");
+                };
+                let entries = mapping_entries(entries, self.duplicate_keys)?;
+                let node = YamlNode::new(YamlObject::Mapping(entries, flow), start.merge(&span));
+                self.end_node(properties, node)?;
+            }
+            EventKind::SequenceStart { anchor, tag, flow } => {
+                self.start_node();
+                self.frames.push(Frame::Sequence {
+                    items: vec![],
+                    leading: vec![],
+                    properties: Properties { anchor, tag },
+                    flow,
+                    start: span,
+                });
+            }
+            EventKind::SequenceEnd => {
+                let Some(Frame::Sequence {
+                    items,
+                    properties,
+                    start,
+                    ..
+                }) = self.frames.pop()
+                else {
+                    unreachable!("sequence end without sequence start");
+                };
+                let node = YamlNode::new(YamlObject::List(items), start.merge(&span));
+                self.end_node(properties, node)?;
+            }
+            EventKind::Scalar {
+                value,
+                style,
+                anchor,
+                tag,
+            } => {
+                self.start_node();
+                if let Some(Frame::Mapping {
+                    key: None,
+                    merge_key,
+                    ..
+                }) = self.frames.last_mut()
+                {
+                    *merge_key = is_merge_key(&value, style, tag.as_ref());
+                }
+                self.end_node(Properties { anchor, tag }, YamlNode::new(value, span))?;
+            }
+            EventKind::Alias(name) => {
+                self.start_node();
+                let Some(node) = self.anchors.get(&name).cloned() else {
+                    return Err(YamlParserError::new(
+                        YamlParserErrorKind::UndefinedAlias,
+                        None,
+                        YamlToken::Alias(name),
+                        span,
+                    ));
+                };
+                self.add_node(YamlNode::new(YamlObject::Reference(name, node), span));
+            }
+            EventKind::Comment {
+                content,
+                trailing: true,
+            } => self.trailing_comment(content),
+            EventKind::Comment { content, .. } => self.comments.push((content, span)),
+        }
+        Ok(())
+    }
+
+    /// Take the comments before the node that start, that annotate
+    /// it when it is the key of a block mapping entry or the item of
+    /// a block sequence, otherwise they are part of the document.
+    fn start_node(&mut self) {
+        match self.frames.last_mut() {
+            Some(Frame::Mapping {
+                flow: false,
+                key: None,
+                leading,
+                ..
+            })
+            | Some(Frame::Sequence {
+                flow: false,
+                leading,
+                ..
+            }) => {
+                *leading = mem::take(&mut self.comments)
+                    .into_iter()
+                    .map(|(content, _)| content)
+                    .collect();
+            }
+            Some(Frame::Document { .. }) => self.flush_comments(),
+            _ => {}
+        }
+    }
+
+    /// Apply the properties to the node just built, and add
+    /// it to the node that contains it.
+    fn end_node(&mut self, properties: Properties, node: YamlNode) -> Result<(), YamlParserError> {
+        let node = match properties.tag {
+            Some((tag, span)) => tag_node(tag, span, node)?,
+            None => node,
+        };
+        let node = self.anchor_node(properties.anchor, node);
+        self.add_node(node);
+        Ok(())
+    }
+
+    /// Share the node built with the anchor given in input, so
+    /// the aliases defined after it can point to the node.
+    fn anchor_node(&mut self, anchor: Option<String>, node: YamlNode) -> YamlNode {
+        let Some(name) = anchor else {
+            return node;
+        };
+        let span = node.span;
+        let node = Rc::new(node);
+        // an anchor can be redefined, and the following aliases
+        // point to the last node defined.
+        self.anchors.insert(name.clone(), node.clone());
+        YamlNode::new(YamlObject::Reference(name, node), span)
+    }
+
+    /// Add the node to the document, the mapping or the
+    /// sequence that contains it.
+    fn add_node(&mut self, mut node: YamlNode) {
+        match self.frames.last_mut() {
+            Some(Frame::Document { nodes, .. }) => nodes.push(node),
+            Some(Frame::Mapping {
+                entries,
+                key,
+                merge_key,
+                leading,
+                ..
+            }) => match key.take() {
+                Some(key) => {
+                    let mut entry = MappingEntry::new(key, node);
+                    entry.comments.leading = mem::take(leading);
+                    entries.push((entry, mem::take(merge_key)));
+                }
+                None => *key = Some(node),
+            },
+            Some(Frame::Sequence { items, leading, .. }) => {
+                node.comments.leading = mem::take(leading);
+                items.push(node);
+            }
+            None => unreachable!("node outside of a document"),
+        }
+    }
+
+    /// Attach the comment to the last entry or item of
+    /// the collection that we are building.
+    fn trailing_comment(&mut self, content: String) {
+        let comments = match self.frames.last_mut() {
+            Some(Frame::Mapping { entries, .. }) => {
+                entries.last_mut().map(|(entry, _)| &mut entry.comments)
+            }
+            Some(Frame::Sequence { items, .. }) => items.last_mut().map(|item| &mut item.comments),
+            _ => None,
+        };
+        if let Some(comments) = comments {
+            comments.trailing = Some(content);
+        }
+    }
+
+    /// Store the comments that do not annotate a node in
+    /// the document.
+    fn flush_comments(&mut self) {
+        let comments = mem::take(&mut self.comments);
+        if let Some(Frame::Document { nodes, .. }) = self.frames.last_mut() {
+            for (content, span) in comments {
+                nodes.push(YamlNode::new(YamlObject::Comment(content), span));
+            }
+        }
+    }
+}
+
+/// Check that the keys of the mapping are unique, following the
+/// policy given in input, and expand the merge keys `<<` of the
+/// mapping, e.g:
+///
+/// base: &base {type: int64}
+/// PropOne: {<<: *base, nullable: true}
+///
+/// The entries of the merged mappings take the place of the merge
+/// key, unless the key is already defined in the mapping or by a
+/// mapping merged before.
+fn mapping_entries(
+    entries: Vec<(MappingEntry, bool)>,
+    duplicate_keys: DuplicateKeys,
+) -> Result<Vec<MappingEntry>, YamlParserError> {
+    // position of each key inside the unique entries, where
+    // the merge key is different from a quoted `"<<"` key
+    let mut positions = HashMap::new();
+    let mut unique: Vec<(MappingEntry, bool)> = vec![];
+    for (entry, merge_key) in entries {
+        let id = (merge_key, key_id(&entry.key));
+        let Some(&pos) = positions.get(&id) else {
+            positions.insert(id, unique.len());
+            unique.push((entry, merge_key));
+            continue;
+        };
+        match duplicate_keys {
+            DuplicateKeys::Reject => {
+                return Err(YamlParserError::new(
+                    YamlParserErrorKind::DuplicateKey,
+                    None,
+                    key_token(&entry.key),
+                    entry.key.span,
+                ))
+            }
+            DuplicateKeys::KeepFirst => {}
+            DuplicateKeys::KeepLast => unique[pos].0.value = entry.value,
+        }
+    }
+    let mut keys: HashSet<String> = positions
+        .into_keys()
+        .filter(|(merge_key, _)| !merge_key)
+        .map(|(_, id)| id)
+        .collect();
+    let mut merged = vec![];
+    for (entry, merge_key) in unique {
+        if !merge_key {
+            merged.push(entry);
+            continue;
+        }
+        for source in merge_sources(&entry)? {
+            if keys.insert(key_id(&source.key)) {
+                merged.push(source.clone());
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// Apply the tag to the node given in input, where the standard
+/// scalar tags force the type of the value, e.g: `!!str 12`, and
+/// the application tags are kept in the tree, e.g: `!ref ModelOne`.
+///
+/// N.B the scanner does not resolve the plain scalars with a tag,
+/// so the content of the scalar is still a string here.
+fn tag_node(tag: String, span: Span, node: YamlNode) -> Result<YamlNode, YamlParserError> {
+    let Some(name) = tag.strip_prefix(YAML_TAG_PREFIX) else {
+        // the non-specific tag `!` keep the node as it is
+        if tag == "!" {
+            return Ok(node);
+        }
+        let node_span = span.merge(&node.span);
+        return Ok(YamlNode::new(
+            YamlObject::Tagged(tag, Box::new(node)),
+            node_span,
+        ));
+    };
+    let (expected, resolved) = match name {
+        "int" | "float" | "bool" | "null" => {
+            let YamlObject::Str(content) = &node.object else {
+                return Err(invalid_tag(&tag, span, "a scalar"));
+            };
+            let resolved = match name {
+                "null" if content.is_empty() => YamlToken::NullVal,
+                _ => resolve_plain_scalar(content),
+            };
+            let object = match (name, resolved) {
+                ("int", YamlToken::IntVal(val)) => Some(YamlObject::Int(val)),
+                ("float", YamlToken::IntVal(val)) => Some(YamlObject::Float(val as f64)),
+                ("float", YamlToken::FloatVal(val)) => Some(YamlObject::Float(val)),
+                ("bool", YamlToken::BoolVal(val)) => Some(YamlObject::Bool(val)),
+                ("null", YamlToken::NullVal) => Some(YamlObject::Null),
+                _ => None,
+            };
+            (name, object)
+        }
+        "str" => match node.object {
+            YamlObject::Str(_) => return Ok(node),
+            _ => (name, None),
+        },
+        // the other standard tags, like `!!seq` and `!!map`,
+        // do not change the node
+        _ => return Ok(node),
+    };
+    match resolved {
+        Some(object) => Ok(YamlNode::new(object, node.span)),
+        None => Err(invalid_tag(
+            &tag,
+            span,
+            &format!("a value of `!!{expected}`"),
+        )),
+    }
+}
+
+/// wrapping the way to build the error of a tag that
+/// can not be applied to the node.
+fn invalid_tag(tag: &str, span: Span, expected: &str) -> YamlParserError {
+    YamlParserError::new(
+        YamlParserErrorKind::InvalidTag,
+        Some(expected),
+        YamlToken::Tag(tag.to_string()),
+        span,
+    )
+}
+
+/// Check if the scalar is the merge key, that is a plain `<<`
+/// or a key with the `!!merge` tag, e.g: `"<<"` is a string.
+fn is_merge_key(value: &YamlObject, style: ScalarStyle, tag: Option<&(String, Span)>) -> bool {
+    match tag {
+        Some((tag, _)) => tag.strip_prefix(YAML_TAG_PREFIX) == Some("merge"),
+        None => {
+            style == ScalarStyle::Plain && matches!(value, YamlObject::Str(key) if key == MERGE_KEY)
+        }
+    }
+}
+
+/// Entries of the mappings merged by the merge key, where the value
+/// can be a mapping, a sequence of mappings or a sequence of aliases
+/// to them, e.g: `<<: [*audit, *owner]`.
+fn merge_sources(entry: &MappingEntry) -> Result<Vec<&MappingEntry>, YamlParserError> {
+    let value = entry.value.resolve();
+    let sources: Vec<&YamlNode> = match &value.object {
+        YamlObject::List(items)
+            if items
+                .iter()
+                .all(|item| matches!(item.object, YamlObject::Reference(..))) =>
+        {
+            items.iter().map(|item| item.resolve()).collect()
+        }
+        _ => vec![value],
+    };
+    let mut entries = vec![];
+    for source in sources {
+        match &source.object {
+            YamlObject::Mapping(source_entries, _) => entries.extend(source_entries),
+            YamlObject::List(items) if items.iter().all(|item| item.entries().is_some()) => entries
+                .extend(
+                    items
+                        .iter()
+                        .flat_map(|item| item.entries().unwrap_or_default()),
+                ),
+            _ => {
+                return Err(YamlParserError::new(
+                    YamlParserErrorKind::InvalidMergeKey,
+                    Some("a mapping or a sequence of mappings"),
+                    YamlToken::Identifier(MERGE_KEY.to_string()),
+                    entry.span(),
+                ))
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Identifier of the key used to compare the keys of a mapping,
+/// where the keys with a different type are different, e.g: `1` and `"1"`.
+///
+/// N.B the identifier does not include the spans of the nodes, so
+/// two collections with the same content are the same key.
+fn key_id(key: &YamlNode) -> String {
+    let mut id = String::new();
+    push_key_id(key, &mut id);
+    id
+}
+
+fn push_key_id(node: &YamlNode, id: &mut String) {
+    match &node.resolve().object {
+        YamlObject::Document(items) | YamlObject::List(items) => {
+            id.push('[');
+            for item in items {
+                push_key_id(item, id);
+                id.push(',');
+            }
+            id.push(']');
+        }
+        YamlObject::Mapping(entries, _) => {
+            id.push('{');
+            for entry in entries {
+                push_key_id(&entry.key, id);
+                id.push(':');
+                push_key_id(&entry.value, id);
+                id.push(',');
+            }
+            id.push('}');
+        }
+        YamlObject::Str(value) => id.push_str(&format!("str:{value:?}")),
+        YamlObject::Int(value) => id.push_str(&format!("int:{value}")),
+        YamlObject::Float(value) => id.push_str(&format!("float:{value:?}")),
+        YamlObject::Bool(value) => id.push_str(&format!("bool:{value}")),
+        YamlObject::Null => id.push_str("null"),
+        YamlObject::Comment(value) => id.push_str(&format!("comment:{value:?}")),
+        YamlObject::Reference(..) | YamlObject::Tagged(..) => unreachable!("resolved node"),
+    }
+}
+
+/// Token of the key given in input, used to report it in the errors.
+fn key_token(key: &YamlNode) -> YamlToken {
+    match &key.resolve().object {
+        YamlObject::Int(val) => YamlToken::IntVal(*val),
+        YamlObject::Float(val) => YamlToken::FloatVal(*val),
+        YamlObject::Bool(val) => YamlToken::BoolVal(*val),
+        YamlObject::Null => YamlToken::NullVal,
+        YamlObject::Str(val) => YamlToken::Identifier(val.to_string()),
+        object => YamlToken::Identifier(format!("{:?}", object)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::key_id;
+    use crate::parser::tokens::{MappingEntry, YamlNode, YamlObject};
+    use crate::span::Span;
+
+    #[test]
+    fn key_id_without_spans() {
+        let node = |object: YamlObject, start: usize| {
+            YamlNode::new(object, Span::new(start, start + 1, 1, start + 1))
+        };
+        let mapping = |start: usize| {
+            let entry = MappingEntry::new(
+                node(YamlObject::Str("a".to_owned()), start),
+                node(
+                    YamlObject::List(vec![node(YamlObject::Int(1), start)]),
+                    start,
+                ),
+            );
+            node(YamlObject::Mapping(vec![entry], true), start)
+        };
+        assert_eq!(key_id(&mapping(0)), key_id(&mapping(12)));
+        assert_eq!(key_id(&mapping(0)), "{str:\"a\":[int:1,],}");
+
+        assert_ne!(
+            key_id(&node(YamlObject::Int(1), 0)),
+            key_id(&node(YamlObject::Str("1".to_owned()), 0))
+        );
+        assert_ne!(
+            key_id(&node(YamlObject::Str("a,".to_owned()), 0)),
+            key_id(&node(YamlObject::Str("a".to_owned()), 0))
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Events produced by the Yaml parser, that describe the
+//! structure of the stream without build the tree of nodes, e.g:
+//!
+//! name: Mark McGwire
+//!
+//! is StreamStart, DocumentStart, MappingStart, Scalar(name),
+//! Scalar(Mark McGwire), MappingEnd, DocumentEnd and StreamEnd.
+//!
+//! author: https://github.com/vincenzopalazzo
+use super::lookahead::Lookahead;
+use super::parser::Parser;
+use super::tokens::YamlObject;
+use crate::span::Span;
+use crate::YamlError;
+
+/// Style used to write a scalar in the content.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScalarStyle {
+    /// Scalar without quotes, e.g: `Mark McGwire`
+    Plain,
+    /// Scalar between single or double quotes, e.g: `"Mark"`
+    Quoted,
+    /// Literal or folded scalar, e.g: `|` and `>`
+    Block,
+}
+
+/// Kind of event produced by the parser.
+#[derive(Debug, Clone)]
+pub enum EventKind {
+    StreamStart,
+    StreamEnd,
+    /// Start of a document, that is explicit when it starts with `---`
+    DocumentStart {
+        explicit: bool,
+    },
+    DocumentEnd,
+    /// Start of a mapping, that is a flow mapping when it is
+    /// between `{` and `}`.
+    MappingStart {
+        anchor: Option<String>,
+        tag: Option<(String, Span)>,
+        flow: bool,
+    },
+    MappingEnd,
+    /// Start of a sequence, that is a flow sequence when it is
+    /// between `[` and `]`.
+    SequenceStart {
+        anchor: Option<String>,
+        tag: Option<(String, Span)>,
+        flow: bool,
+    },
+    SequenceEnd,
+    /// Scalar with the value resolved by the scanner, e.g: `Int(65)`,
+    /// the tag is expanded with the `%TAG` directives with the
+    /// span where it is defined.
+    Scalar {
+        value: YamlObject,
+        style: ScalarStyle,
+        anchor: Option<String>,
+        tag: Option<(String, Span)>,
+    },
+    /// Alias to a node with an anchor defined before, e.g: `*audit`
+    Alias(String),
+    /// Comment without the `#`, that is trailing when it is on the same
+    /// line of the mapping entry or the sequence item that precede it,
+    /// otherwise it annotates the entry or the item that follow it.
+    Comment {
+        content: String,
+        trailing: bool,
+    },
+}
+
+/// Event with the position of the content that produced it,
+/// where the end of a collection has the span of its last token.
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub kind: EventKind,
+    pub span: Span,
+}
+
+impl Event {
+    pub fn new(kind: EventKind, span: Span) -> Self {
+        Event { kind, span }
+    }
+}
+
+/// Iterator over the events of a stream of tokens, where the parser
+/// reads the tokens and produces the events only when the next
+/// event is pulled, e.g: with the tokens of `Scanner::tokens` the
+/// content is scanned while the events are consumed.
+///
+/// The parser keeps only the states of the collections that contain
+/// the current node and the tokens read ahead of the current one,
+/// so the memory grows with the nesting of the nodes and not with
+/// the size of the document.
+///
+/// The iterator stops after the first error.
+pub struct Events<'a> {
+    parser: &'a mut Parser,
+    tokens: Lookahead<'a>,
+    done: bool,
+}
+
+impl<'a> Events<'a> {
+    pub(crate) fn new(parser: &'a mut Parser, tokens: Lookahead<'a>) -> Self {
+        Events {
+            parser,
+            tokens,
+            done: false,
+        }
+    }
+}
+
+impl Iterator for Events<'_> {
+    type Item = Result<Event, YamlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.parser.next_event(&mut self.tokens) {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::events::{EventKind, ScalarStyle};
+    use crate::parser::parser::Parser;
+    use crate::parser::tokens::YamlObject;
+    use crate::scanner::{scanner::Scanner, YamlScanner};
+    use crate::YamlError;
+
+    use indoc::indoc;
+
+    /// Short name of the event, used to compare
+    /// the events with the ones expected.
+    fn name(kind: &EventKind) -> String {
+        match kind {
+            EventKind::StreamStart => "+STR".to_owned(),
+            EventKind::StreamEnd => "-STR".to_owned(),
+            EventKind::DocumentStart { explicit: true } => "+DOC ---".to_owned(),
+            EventKind::DocumentStart { explicit: false } => "+DOC".to_owned(),
+            EventKind::DocumentEnd => "-DOC".to_owned(),
+            EventKind::MappingStart { flow: true, .. } => "+MAP {}".to_owned(),
+            EventKind::MappingStart { anchor, .. } => match anchor {
+                Some(anchor) => format!("+MAP &{anchor}"),
+                None => "+MAP".to_owned(),
+            },
+            EventKind::MappingEnd => "-MAP".to_owned(),
+            EventKind::SequenceStart { flow: true, .. } => "+SEQ []".to_owned(),
+            EventKind::SequenceStart { .. } => "+SEQ".to_owned(),
+            EventKind::SequenceEnd => "-SEQ".to_owned(),
+            EventKind::Scalar { value, .. } => format!("=VAL {:?}", value),
+            EventKind::Alias(name) => format!("=ALI *{name}"),
+            EventKind::Comment { content, .. } => format!("#{content}"),
+        }
+    }
+
+    #[test]
+    fn parse_events() {
+        let content = indoc! {r#"
+        # Users of the system
+        base: &base {type: int64}
+        names:
+          - Mark # first
+          - *base
+        "#};
+        let tokens = Scanner::new().scan(content).unwrap();
+        let mut parser = Parser::new();
+        let events: Vec<String> = parser
+            .events(&tokens)
+            .map(|event| name(&event.unwrap().kind))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                "+STR",
+                "+DOC",
+                "+MAP",
+                "# Users of the system",
+                "=VAL Str(\"base\")",
+                "+MAP {}",
+                "=VAL Str(\"type\")",
+                "=VAL Str(\"int64\")",
+                "-MAP",
+                "=VAL Str(\"names\")",
+                "+SEQ",
+                "=VAL Str(\"Mark\")",
+                "# first",
+                "=ALI *base",
+                "-SEQ",
+                "-MAP",
+                "-DOC",
+                "-STR",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_scalar_events() {
+        let content = "plain: a\nquoted: \"b\"\nblock: |\n  c\ntagged: !!str 12\n";
+        let tokens = Scanner::new().scan(content).unwrap();
+        let mut parser = Parser::new();
+        let scalars: Vec<(ScalarStyle, Option<String>)> = parser
+            .events(&tokens)
+            .filter_map(|event| match event.unwrap().kind {
+                EventKind::Scalar { style, tag, .. } => Some((style, tag.map(|(tag, _)| tag))),
+                _ => None,
+            })
+            // skip the keys
+            .skip(1)
+            .step_by(2)
+            .collect();
+        assert_eq!(
+            scalars,
+            vec![
+                (ScalarStyle::Plain, None),
+                (ScalarStyle::Quoted, None),
+                (ScalarStyle::Block, None),
+                (
+                    ScalarStyle::Plain,
+                    Some("tag:yaml.org,2002:str".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn pull_first_document() {
+        // the second document is parsed only when its events are pulled
+        let content = "name: first\n---\nname: *undefined\n";
+        let tokens = Scanner::new().scan(content).unwrap();
+        let mut parser = Parser::new();
+        let name = parser
+            .events(&tokens)
+            .map(|event| event.unwrap().kind)
+            .take_while(|kind| !matches!(kind, EventKind::DocumentEnd))
+            .filter_map(|kind| match kind {
+                EventKind::Scalar {
+                    value: YamlObject::Str(value),
+                    ..
+                } => Some(value),
+                _ => None,
+            })
+            .last();
+        assert_eq!(name, Some("first".to_string()));
+
+        let err = parser.events(&tokens).find_map(|event| event.err());
+        assert!(err.is_some());
+    }
+
+    #[test]
+    fn pull_events_from_scanner() {
+        // the content after the first document is scanned only
+        // when the events of the second document are pulled
+        let content = "name: first\n---\nname: \"unterminated\n";
+        let mut parser = Parser::new();
+        let mut events = parser.events_from(Scanner::new().tokens(content));
+        let first: Vec<EventKind> = events
+            .by_ref()
+            .map(|event| event.unwrap().kind)
+            .take_while(|kind| !matches!(kind, EventKind::DocumentEnd))
+            .collect();
+        assert_eq!(first.len(), 6);
+
+        let err = events.find_map(|event| event.err());
+        assert!(matches!(err, Some(YamlError::Scanner(_))));
+        assert!(events.next().is_none());
+    }
+}
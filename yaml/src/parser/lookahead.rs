@@ -0,0 +1,124 @@
+//! Stream of tokens read by the parser, where the tokens
+//! are pulled from the scanner only when the parser needs
+//! them, e.g: to check if the current token is a key.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::collections::VecDeque;
+
+use crate::scanner::tokens::{Token, YamlToken};
+use crate::scanner::ScannerError;
+use crate::span::Span;
+
+/// Stream of tokens with the lookahead of the parser, that keeps
+/// in memory only the tokens read after the current position.
+///
+/// The first error of the scanner ends the stream, and it is
+/// kept until the parser reports it.
+pub(crate) struct Lookahead<'a> {
+    source: Box<dyn Iterator<Item = Result<Token, ScannerError>> + 'a>,
+    /// tokens read from the scanner and not consumed yet
+    buffer: VecDeque<Token>,
+    /// number of tokens consumed
+    pos: usize,
+    /// span of the last token consumed
+    last: Option<Span>,
+    /// span of the last token consumed that is not a space
+    last_content: Option<Span>,
+    /// span of the last token consumed that is not a space
+    /// or a comment, that is the end of the last node
+    last_node: Option<Span>,
+    /// span of the last token read from the scanner
+    last_read: Option<Span>,
+    error: Option<ScannerError>,
+}
+
+impl<'a> Lookahead<'a> {
+    pub(crate) fn new<I>(source: I) -> Self
+    where
+        I: Iterator<Item = Result<Token, ScannerError>> + 'a,
+    {
+        Lookahead {
+            source: Box::new(source),
+            buffer: VecDeque::new(),
+            pos: 0,
+            last: None,
+            last_content: None,
+            last_node: None,
+            last_read: None,
+            error: None,
+        }
+    }
+
+    /// Token at the distance given in input from the current
+    /// position, that is read from the scanner if it is needed.
+    pub(crate) fn peek(&mut self, distance: usize) -> Option<&Token> {
+        while self.buffer.len() <= distance && self.error.is_none() {
+            match self.source.next() {
+                Some(Ok(token)) => {
+                    self.last_read = Some(token.span);
+                    self.buffer.push_back(token);
+                }
+                Some(Err(err)) => self.error = Some(err),
+                None => break,
+            }
+        }
+        self.buffer.get(distance)
+    }
+
+    /// Consume the token in the current position.
+    pub(crate) fn consume(&mut self) {
+        if self.peek(0).is_none() {
+            return;
+        }
+        let Some(token) = self.buffer.pop_front() else {
+            return;
+        };
+        self.pos += 1;
+        self.last = Some(token.span);
+        if !is_blank(&token.kind) {
+            self.last_content = Some(token.span);
+            if !matches!(token.kind, YamlToken::Pount(_)) {
+                self.last_node = Some(token.span);
+            }
+        }
+    }
+
+    /// Span of the token in the current position, or the span
+    /// of the last token if the stream is already consumed.
+    pub(crate) fn span(&mut self) -> Span {
+        match self.peek(0) {
+            Some(token) => token.span,
+            None => self.last_read.unwrap_or_default(),
+        }
+    }
+
+    /// Number of tokens consumed.
+    pub(crate) fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Span of the last token consumed.
+    pub(crate) fn last(&self) -> Option<Span> {
+        self.last
+    }
+
+    /// Span of the last token consumed that is not a space.
+    pub(crate) fn last_content(&self) -> Option<Span> {
+        self.last_content
+    }
+
+    /// Span of the last token consumed that is part of a node.
+    pub(crate) fn last_node(&self) -> Option<Span> {
+        self.last_node
+    }
+
+    /// Take the error of the scanner that ended the stream.
+    pub(crate) fn take_error(&mut self) -> Option<ScannerError> {
+        self.error.take()
+    }
+}
+
+/// Check if the token is a space or a tab between the tokens.
+pub(crate) fn is_blank(token: &YamlToken) -> bool {
+    matches!(token, YamlToken::Space | YamlToken::TabSpace)
+}
@@ -3,28 +3,128 @@
 //! author: https://github.com/vincenzopalazzo
 use std::error::Error;
 use std::fmt;
-use std::fmt::{write, Formatter};
+use std::fmt::Formatter;
 use std::vec::Vec;
 
-use crate::scanner::tokens::YamlToken;
+use crate::scanner::tokens::{Token, YamlToken};
+use crate::span::Span;
 
+mod builder;
+pub mod events;
+mod lookahead;
+pub mod options;
+#[allow(clippy::module_inception)]
 pub mod parser;
 pub mod tokens;
 
 /// Interface Yaml Interface
-trait YamlParser<T> {
-    fn parse(&mut self, tokens: &Vec<YamlToken>) -> &Vec<T>;
+pub trait YamlParser<T> {
+    fn parse(&mut self, tokens: &[Token]) -> Result<Vec<T>, YamlParserError>;
 }
 
-#[derive(Debug)]
+/// Kind of error that the parser can find in the token stream
+#[derive(Debug, Clone, PartialEq)]
+pub enum YamlParserErrorKind {
+    /// Token that is not valid in the current position
+    UnexpectedToken,
+    /// Indentation that does not match the current scope
+    BadIndentation,
+    /// The token stream ended before the end of the node
+    UnexpectedEof,
+    /// `%YAML` or `%TAG` directive that is not well formed
+    InvalidDirective,
+    /// Alias to an anchor that is not defined before it
+    UndefinedAlias,
+    /// Alias to an anchor that contains the alias itself
+    RecursiveAlias,
+    /// Merge key `<<` with a value that is not a mapping
+    InvalidMergeKey,
+    /// Key defined more than once in the same mapping
+    DuplicateKey,
+    /// Tag with an undeclared handle, or that does not
+    /// match the value of the node
+    InvalidTag,
+    /// Collections nested more than the max depth
+    DepthLimitExceeded,
+    /// Content bigger than the max input size
+    SizeLimitExceeded,
+    /// Stream with more nodes than the max number of nodes
+    NodeLimitExceeded,
+    /// Aliases that expand more nodes than the max alias expansion
+    AliasLimitExceeded,
+}
+
+impl fmt::Display for YamlParserErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            YamlParserErrorKind::UnexpectedToken => write!(f, "unexpected token"),
+            YamlParserErrorKind::BadIndentation => write!(f, "bad indentation"),
+            YamlParserErrorKind::UnexpectedEof => write!(f, "unexpected end of file"),
+            YamlParserErrorKind::InvalidDirective => write!(f, "invalid directive"),
+            YamlParserErrorKind::UndefinedAlias => write!(f, "undefined alias"),
+            YamlParserErrorKind::RecursiveAlias => write!(f, "recursive alias"),
+            YamlParserErrorKind::InvalidMergeKey => write!(f, "invalid merge key"),
+            YamlParserErrorKind::DuplicateKey => write!(f, "duplicate key"),
+            YamlParserErrorKind::InvalidTag => write!(f, "invalid tag"),
+            YamlParserErrorKind::DepthLimitExceeded => write!(f, "depth limit exceeded"),
+            YamlParserErrorKind::SizeLimitExceeded => write!(f, "size limit exceeded"),
+            YamlParserErrorKind::NodeLimitExceeded => write!(f, "node limit exceeded"),
+            YamlParserErrorKind::AliasLimitExceeded => write!(f, "alias expansion limit exceeded"),
+        }
+    }
+}
+
+/// Parser error with the token found and the position
+/// of it inside the content.
+#[derive(Debug, Clone, PartialEq)]
 pub struct YamlParserError {
-    cause: String,
+    pub kind: YamlParserErrorKind,
+    /// what the parser was expecting, e.g: "`:`"
+    pub expected: Option<String>,
+    /// the token found in the stream
+    pub found: YamlToken,
+    /// position of the token found
+    pub span: Span,
+}
+
+impl YamlParserError {
+    pub fn new(
+        kind: YamlParserErrorKind,
+        expected: Option<&str>,
+        found: YamlToken,
+        span: Span,
+    ) -> Self {
+        YamlParserError {
+            kind,
+            expected: expected.map(|expected| expected.to_string()),
+            found,
+            span,
+        }
+    }
+
+    /// Message of the error without the position, e.g:
+    /// "unexpected `foo`, expected `:`".
+    pub fn message(&self) -> String {
+        let mut message = match self.kind {
+            YamlParserErrorKind::UnexpectedToken => format!("unexpected {}", self.found),
+            YamlParserErrorKind::UndefinedAlias
+            | YamlParserErrorKind::RecursiveAlias
+            | YamlParserErrorKind::DuplicateKey => {
+                format!("{} {}", self.kind, self.found)
+            }
+            _ => self.kind.to_string(),
+        };
+        if let Some(expected) = &self.expected {
+            message.push_str(&format!(", expected {}", expected));
+        }
+        message
+    }
 }
 
 impl Error for YamlParserError {}
 
 impl fmt::Display for YamlParserError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.cause)
+        write!(f, "{} at {}", self.message(), self.span)
     }
 }
@@ -0,0 +1,113 @@
+//! Options of the Yaml parser, built with a chain
+//! of calls, e.g:
+//!
+//! let options = ParseOptions::new().strict(true).max_depth(32);
+//!
+//! author: https://github.com/vincenzopalazzo
+use utils::tracer::{DummyTracer, Tracer};
+
+/// Default max number of nested collections.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Default max size of the content, in bytes.
+pub const DEFAULT_MAX_INPUT_SIZE: usize = 16 * 1024 * 1024;
+
+/// Default max number of nodes in a stream.
+pub const DEFAULT_MAX_NODES: usize = 1_000_000;
+
+/// Default max number of nodes expanded by the aliases in a stream.
+pub const DEFAULT_MAX_ALIAS_EXPANSION: usize = 100_000;
+
+/// How the parser handles a key defined more
+/// than once in the same mapping.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DuplicateKeys {
+    /// The duplicate key is an error
+    #[default]
+    Reject,
+    /// The first entry with the key is kept
+    KeepFirst,
+    /// The value of the last entry with the key takes the
+    /// place of the first one
+    KeepLast,
+}
+
+/// Options of the parser, where the default is a lenient parser
+/// that rejects the duplicate keys, with limits that protect
+/// from the content that is too big to parse.
+#[derive(Debug)]
+pub struct ParseOptions {
+    pub(crate) tracer: Box<dyn Tracer>,
+    pub(crate) strict: bool,
+    pub(crate) max_depth: usize,
+    pub(crate) max_input_size: usize,
+    pub(crate) max_nodes: usize,
+    pub(crate) max_alias_expansion: usize,
+    pub(crate) duplicate_keys: DuplicateKeys,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            tracer: Box::new(DummyTracer {}),
+            strict: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_input_size: DEFAULT_MAX_INPUT_SIZE,
+            max_nodes: DEFAULT_MAX_NODES,
+            max_alias_expansion: DEFAULT_MAX_ALIAS_EXPANSION,
+            duplicate_keys: DuplicateKeys::default(),
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tracer that log the steps of the parser.
+    pub fn tracer(mut self, tracer: Box<dyn Tracer>) -> Self {
+        self.tracer = tracer;
+        self
+    }
+
+    /// Reject the unknown directives and the `%YAML` versions newer
+    /// than 1.2, that are otherwise ignored with a trace.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Max number of nested collections in a document.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Max size of the content in bytes, that is checked
+    /// by `parse_str` before scanning the content.
+    pub fn max_input_size(mut self, max_input_size: usize) -> Self {
+        self.max_input_size = max_input_size;
+        self
+    }
+
+    /// Max number of nodes in the stream, where the
+    /// aliases count as a single node.
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    /// Max number of nodes in the stream that are expanded by the
+    /// aliases, where each alias expands all the nodes of its anchor.
+    pub fn max_alias_expansion(mut self, max_alias_expansion: usize) -> Self {
+        self.max_alias_expansion = max_alias_expansion;
+        self
+    }
+
+    /// Policy for the keys defined more than once in a mapping.
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+}
@@ -2,417 +2,1417 @@
 //! of the Yaml parser
 //!
 //! author: https://github.com/vincenzopalazzo
-use std::error::Error;
+use std::collections::{HashMap, VecDeque};
+use std::mem;
 use std::vec::Vec;
 
-use super::tokens::{YamlObject, YamlScope};
+use super::builder::TreeBuilder;
+use super::events::{Event, EventKind, Events, ScalarStyle};
+use super::lookahead::{is_blank, Lookahead};
+use super::options::{DuplicateKeys, ParseOptions};
+use super::tokens::{YamlNode, YamlObject};
 use super::YamlParser;
-use crate::parser::YamlParserError;
-use crate::scanner::tokens::YamlToken;
-use utils::tracer::{DummyTracer, Tracer};
+use crate::parser::{YamlParserError, YamlParserErrorKind};
+use crate::scanner::tokens::{Token, YamlToken};
+use crate::scanner::ScannerError;
+use crate::span::Span;
+use crate::YamlError;
+use utils::tracer::Tracer;
+
+/// State of the parser, that is what the parser expects in the
+/// current position of the stream. The states of the collections
+/// that contain the current node are kept in a stack, and each
+/// state produces the events of the next node or the end of
+/// the collection.
+#[derive(Debug)]
+enum State {
+    StreamStart,
+    /// Start of the next document, or the end of the stream
+    DocumentStart,
+    /// Root node of the document, or the end of the document
+    DocumentContent {
+        explicit: bool,
+        has_root: bool,
+        start: Span,
+    },
+    /// Next entry of a block mapping, or the end of the mapping
+    BlockMapping {
+        indent: usize,
+        first: bool,
+        start: Span,
+        anchor: Option<String>,
+    },
+    /// Next item of a block sequence, or the end of the sequence
+    BlockSequence {
+        indent: usize,
+        first: bool,
+        start: Span,
+        anchor: Option<String>,
+    },
+    /// Comment on the line of the entry or the item, that is
+    /// emitted after its value
+    Trailing(Option<(String, Span)>),
+    /// Comment after a value on the same line of the entry
+    /// or the item, that is parsed after the value
+    TrailingComment,
+    /// Next item of a flow sequence, or `]`
+    FlowSequence {
+        start: Span,
+        anchor: Option<String>,
+    },
+    /// `,` or `]` after an item of a flow sequence
+    FlowSequenceNext {
+        start: Span,
+        anchor: Option<String>,
+    },
+    /// Next entry of a flow mapping, or `}`
+    FlowMapping {
+        start: Span,
+        anchor: Option<String>,
+    },
+    /// `,` or `}` after an entry of a flow mapping
+    FlowMappingNext {
+        start: Span,
+        anchor: Option<String>,
+    },
+}
 
 #[derive(Debug)]
 pub struct Parser {
-    ir: Vec<YamlObject>,
-    current_pos: u32,
-    current_scope_size: Option<u32>,
-    indentation_size: u16,
+    /// events produced by the last state, that are not
+    /// consumed yet
+    events: VecDeque<Event>,
+    /// states of the collections that contain the current node,
+    /// from the root node to the current one
+    states: Vec<State>,
+    /// handles declared with the `%TAG` directive in the
+    /// document that we are parsing
+    tag_handles: HashMap<String, String>,
+    /// anchors of the nodes already parsed in the document
+    /// that we are parsing, with the number of nodes that
+    /// an alias to them expands
+    anchors: HashMap<String, usize>,
+    /// anchors of the nodes that we are parsing, an alias
+    /// to them is a cycle, with the number of nodes counted
+    /// when the anchor is found
+    pending_anchors: Vec<(String, usize)>,
+    /// indentation of the block collections that we are
+    /// parsing, from the root node to the current one
+    indents: Vec<usize>,
+    /// comments skipped before the next mapping entry or
+    /// sequence item, that are attached to it
+    comments: Vec<(String, Span)>,
+    /// number of collections that we are parsing
+    depth: usize,
+    /// number of nodes parsed in the stream
+    nodes: usize,
+    /// number of nodes expanded by the aliases in the stream
+    aliased: usize,
+    strict: bool,
+    max_depth: usize,
+    max_nodes: usize,
+    max_alias_expansion: usize,
+    duplicate_keys: DuplicateKeys,
     tracer: Box<dyn Tracer>,
 }
 
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Core implementation of the Yaml Parser.
 impl Parser {
-    fn new() -> Self {
-        Parser::new_with_tracer(Box::new(DummyTracer {}))
+    pub fn new() -> Self {
+        Parser::new_with_options(ParseOptions::default())
     }
 
-    fn new_with_tracer(tracer: Box<dyn Tracer>) -> Self {
+    pub fn new_with_tracer(tracer: Box<dyn Tracer>) -> Self {
+        Parser::new_with_options(ParseOptions::default().tracer(tracer))
+    }
+
+    pub fn new_with_options(options: ParseOptions) -> Self {
         Parser {
-            ir: vec![],
-            current_pos: 0,
-            current_scope_size: None,
-            indentation_size: 0,
-            tracer: tracer,
+            events: VecDeque::new(),
+            states: vec![],
+            tag_handles: HashMap::new(),
+            anchors: HashMap::new(),
+            pending_anchors: vec![],
+            indents: vec![],
+            comments: vec![],
+            depth: 0,
+            nodes: 0,
+            aliased: 0,
+            strict: options.strict,
+            max_depth: options.max_depth,
+            max_nodes: options.max_nodes,
+            max_alias_expansion: options.max_alias_expansion,
+            duplicate_keys: options.duplicate_keys,
+            tracer: options.tracer,
+        }
+    }
+
+    /// Iterate over the events of the tokens already scanned,
+    /// without build the tree of nodes, e.g:
+    ///
+    /// for event in parser.events(&tokens) {
+    ///     println!("{:?}", event?.kind);
+    /// }
+    pub fn events<'a>(&'a mut self, tokens: &'a [Token]) -> Events<'a> {
+        self.events_from(tokens.iter().cloned().map(Ok))
+    }
+
+    /// Iterate over the events of the tokens pulled from the
+    /// scanner, where the content is scanned while the events
+    /// are consumed, e.g:
+    ///
+    /// for event in parser.events_from(Scanner::new().tokens(content)) {
+    ///     println!("{:?}", event?.kind);
+    /// }
+    pub fn events_from<'a, I>(&'a mut self, tokens: I) -> Events<'a>
+    where
+        I: Iterator<Item = Result<Token, ScannerError>> + 'a,
+    {
+        self.nodes = 0;
+        self.aliased = 0;
+        self.indents.clear();
+        self.comments.clear();
+        self.events.clear();
+        self.states.clear();
+        self.states.push(State::StreamStart);
+        Events::new(self, Lookahead::new(tokens))
+    }
+
+    /// Parse the tokens pulled from the scanner, and return a
+    /// `YamlObject::Document` node for each document found, where
+    /// the first error of the scanner or the parser is returned.
+    pub fn parse_tokens<'a, I>(&'a mut self, tokens: I) -> Result<Vec<YamlNode>, YamlError>
+    where
+        I: Iterator<Item = Result<Token, ScannerError>> + 'a,
+    {
+        let mut builder = TreeBuilder::new(self.duplicate_keys);
+        for event in self.events_from(tokens) {
+            builder.push(event?)?;
+        }
+        Ok(builder.documents())
+    }
+
+    /// Run the states of the parser until the next event,
+    /// or return `None` at the end of the stream.
+    pub(crate) fn next_event(
+        &mut self,
+        tokens: &mut Lookahead,
+    ) -> Result<Option<Event>, YamlError> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(Some(event));
+            }
+            let Some(state) = self.states.pop() else {
+                return Ok(None);
+            };
+            let parsed = self.parse_state(state, tokens);
+            // the parser finds the end of the stream after
+            // an error of the scanner
+            if let Some(err) = tokens.take_error() {
+                return Err(err.into());
+            }
+            parsed?;
+        }
+    }
+
+    /// Parse the tokens expected by the state given in input, that
+    /// emits the events of a node and push the state of the next one.
+    fn parse_state(&mut self, state: State, tokens: &mut Lookahead) -> Result<(), YamlParserError> {
+        match state {
+            State::StreamStart => {
+                self.emit(EventKind::StreamStart, Span::default());
+                self.states.push(State::DocumentStart);
+            }
+            State::DocumentStart => self.parse_document_start(tokens)?,
+            State::DocumentContent {
+                explicit,
+                has_root,
+                start,
+            } => self.parse_document_content(explicit, has_root, start, tokens)?,
+            State::BlockMapping {
+                indent,
+                first,
+                start,
+                anchor,
+            } => self.parse_block_mapping_entry(indent, first, start, anchor, tokens)?,
+            State::BlockSequence {
+                indent,
+                first,
+                start,
+                anchor,
+            } => self.parse_block_sequence_item(indent, first, start, anchor, tokens)?,
+            State::Trailing(trailing) => self.emit_trailing(trailing),
+            State::TrailingComment => {
+                let trailing = self.parse_trailing_comment(tokens);
+                self.emit_trailing(trailing);
+            }
+            State::FlowSequence { start, anchor } => {
+                self.parse_flow_sequence_item(start, anchor, tokens)?
+            }
+            State::FlowSequenceNext { start, anchor } => {
+                self.skip_flow_spaces(tokens);
+                match self.take(tokens) {
+                    YamlToken::Comma => self.consume(tokens),
+                    YamlToken::RightSquareBrace => {}
+                    _ => return Err(self.unexpected(tokens, Some("`,` or `]`"))),
+                }
+                self.states.push(State::FlowSequence { start, anchor });
+            }
+            State::FlowMapping { start, anchor } => {
+                self.parse_flow_mapping_entry(start, anchor, tokens)?
+            }
+            State::FlowMappingNext { start, anchor } => {
+                self.skip_flow_spaces(tokens);
+                match self.take(tokens) {
+                    YamlToken::Comma => self.consume(tokens),
+                    YamlToken::RightCurlyBrace => {}
+                    _ => return Err(self.unexpected(tokens, Some("`,` or `}`"))),
+                }
+                self.states.push(State::FlowMapping { start, anchor });
+            }
         }
+        Ok(())
     }
 
-    /// Walk through the list of tokens
-    fn walk(&mut self, tokens: &Vec<YamlToken>) {
-        while !self.is_the_end(tokens) {
-            self.parse_document(tokens);
+    /// Parse the start of the YAML document, that can start with the
+    /// directives and the `---` token, or emit the end of the stream.
+    fn parse_document_start(&mut self, tokens: &mut Lookahead) -> Result<(), YamlParserError> {
+        if self.is_the_end(tokens) {
+            let span = tokens.span();
+            self.emit(EventKind::StreamEnd, span);
+            return Ok(());
+        }
+        self.tracer.info("Start parsing Yaml");
+        let start = self.span(tokens);
+        self.indents.clear();
+        self.depth = 0;
+        self.tag_handles.clear();
+        // the anchors are defined only inside the document
+        self.anchors.clear();
+        self.pending_anchors.clear();
+        let explicit = self.parse_directives(tokens)?;
+        // the comments before the `---` are about the document
+        if explicit {
+            self.emit(EventKind::DocumentStart { explicit: true }, start);
+            self.flush_comments();
         }
+        self.states.push(State::DocumentContent {
+            explicit,
+            has_root: false,
+            start,
+        });
+        Ok(())
     }
 
-    /// Parse the YAML document
-    fn parse_document(&mut self, tokens: &Vec<YamlToken>) {
-        self.tracer.info(&"Start parsing Yaml".to_string());
-        // Open the scope
-        while !self.is_the_end(tokens) {
+    /// Parse the root node of the document, and the end of the document
+    /// that is the `...` token, the start of a new document or the end
+    /// of the stream.
+    fn parse_document_content(
+        &mut self,
+        explicit: bool,
+        has_root: bool,
+        start: Span,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        loop {
+            self.skip_spaces(tokens);
             let token = self.take(tokens);
             self.tracer
                 .info(&format!("Parse document start with token: {:?}", token));
-            // Now, there are two way to start a document in yaml
-            // on with the start docs symbol, that is represented with `----`
-            // or with a plain identifier.
             match token {
-                YamlToken::StartDoc => {
-                    // consume the token where we are matching!
+                // a new document start, or the current document end
+                YamlToken::EOF | YamlToken::StartDoc | YamlToken::Directive(_) => break,
+                YamlToken::EndDoc => {
                     self.consume(tokens);
-                    match self.next(tokens) {
-                        YamlToken::Identifier(name) => {
-                            let yaml_identifier = self.parse_identifier(name.as_str(), tokens);
-                            self.add_to_ir(yaml_identifier);
+                    break;
+                }
+                YamlToken::Pount(_) => self.parse_comment(tokens),
+                _ if !has_root => {
+                    self.states.push(State::DocumentContent {
+                        explicit,
+                        has_root: true,
+                        start,
+                    });
+                    if !explicit {
+                        self.emit(EventKind::DocumentStart { explicit: false }, start);
+                    }
+                    let indent = self.indent(tokens);
+                    return self.parse_block_node(indent, NodeProperties::default(), tokens);
+                }
+                _ => return Err(self.unexpected(tokens, Some("a key or `---`"))),
+            }
+        }
+        self.states.push(State::DocumentStart);
+        // a stream that contains only comments does not contains a document
+        if !explicit && !has_root {
+            self.comments.clear();
+            return Ok(());
+        }
+        self.flush_comments();
+        self.end_node(tokens, EventKind::DocumentEnd, None, start);
+        Ok(())
+    }
+
+    /// Parse the directives and the comments before the start of
+    /// the document, and return true if the document start with `---`.
+    fn parse_directives(&mut self, tokens: &mut Lookahead) -> Result<bool, YamlParserError> {
+        let mut has_directives = false;
+        let mut yaml_version = false;
+        loop {
+            match self.take(tokens) {
+                YamlToken::Pount(_) => self.parse_comment(tokens),
+                YamlToken::Space => self.consume(tokens),
+                YamlToken::Directive(directive) => {
+                    has_directives = true;
+                    let mut parts = directive.split_whitespace();
+                    match parts.next() {
+                        Some("YAML") => {
+                            let version = parts.next().unwrap_or_default();
+                            let Some(minor) = version.strip_prefix("1.") else {
+                                return Err(self.invalid_directive(tokens, "one `%YAML 1.x`"));
+                            };
+                            if yaml_version {
+                                return Err(self.invalid_directive(tokens, "one `%YAML 1.x`"));
+                            }
+                            // the newer versions are parsed as the version 1.2
+                            if minor.parse::<u32>().map_or(true, |minor| minor > 2) {
+                                if self.strict {
+                                    return Err(self.invalid_directive(tokens, "`%YAML 1.2`"));
+                                }
+                                self.tracer.info(&format!(
+                                    "Parse the YAML version {} as the version 1.2",
+                                    version
+                                ));
+                            }
+                            yaml_version = true;
+                        }
+                        Some("TAG") => {
+                            let (Some(handle), Some(prefix)) = (parts.next(), parts.next()) else {
+                                return Err(
+                                    self.invalid_directive(tokens, "`%TAG <handle> <prefix>`")
+                                );
+                            };
+                            let valid_handle = handle.starts_with('!') && handle.ends_with('!');
+                            if !valid_handle || self.tag_handles.contains_key(handle) {
+                                return Err(self.invalid_directive(
+                                    tokens,
+                                    "a unique `!`, `!!` or `!name!` handle",
+                                ));
+                            }
+                            self.tag_handles
+                                .insert(handle.to_string(), prefix.to_string());
                         }
-                        _ => {
-                            self.tracer
-                                .info(&format!("Bad token: {:?}", self.take(tokens)));
-                            self.tracer.info(&format!("Parser Status: {:?}", self));
-                            panic!("Document bad formatted: {:?}", self.take(tokens));
+                        _ if self.strict => {
+                            return Err(self.invalid_directive(tokens, "`%YAML` or `%TAG`"))
                         }
+                        _ => self
+                            .tracer
+                            .info(&format!("Ignore the unknown directive `%{}`", directive)),
                     }
-                }
-                YamlToken::Identifier(name) => {
                     self.consume(tokens);
-                    let yaml_identifier = self.parse_identifier(name.as_str(), tokens);
-                    self.add_to_ir(yaml_identifier);
                 }
-                YamlToken::Pount(_) => self.parse_comment(tokens),
-                YamlToken::EOF => self.consume(tokens),
-                YamlToken::Space => {
-                    // Fallback case, in this block we check if we are still in the current scope
-                    // or we are in a new scope, and this mean that we need to close the prev scope.
-                    self.consume_or_close_scope(tokens);
+                YamlToken::StartDoc => {
+                    self.consume(tokens);
+                    return Ok(true);
                 }
-                _ => panic!(
-                    "Document bad formatted: {:?}\n\n Parse {:?} \n\nTokens {:?}",
-                    self.take(tokens),
-                    self,
-                    tokens
-                ),
+                // the directives must be followed by the `---` token
+                _ if has_directives => return Err(self.unexpected(tokens, Some("`---`"))),
+                _ => return Ok(false),
             }
         }
+    }
 
-        if !self.is_end_of_doc(tokens) && !self.is_the_end(tokens) {
-            panic!(
-                "The document it is no ended, we have the token in the stream {:?}",
-                self.take(tokens)
-            );
-        }
+    /// Build the error for a malformed directive
+    fn invalid_directive(&self, tokens: &mut Lookahead, expected: &str) -> YamlParserError {
+        self.error(
+            tokens,
+            YamlParserErrorKind::InvalidDirective,
+            Some(expected),
+        )
     }
 
-    fn parse_identifier(&mut self, ref_id: &str, tokens: &Vec<YamlToken>) -> YamlObject {
-        assert_eq!(self.next(tokens), YamlToken::DotDot);
-        // TODO: check the case where the literal is not a list but is a value
-        if self.open_scope_if_needed(tokens) {
-            self.consume_scope(tokens);
-            self.tracer.info(&format!(
-                "In identifier: open scope, last token: {:?}",
-                self.take(tokens)
-            ));
-            return self.parse_mapping(&ref_id, tokens);
-        }
+    /// Parse the entry of a block mapping, where the key is at the
+    /// indentation given in input, e.g:
+    ///
+    /// national: [New York Mets, Chicago Cubs]
+    /// american:
+    ///   - Boston Red Sox
+    fn parse_mapping_entry(
+        &mut self,
+        indent: usize,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        self.flush_comments();
+        let key = self.take(tokens);
+        self.parse_key(tokens, "a mapping key")?;
+        self.skip_inline_spaces(tokens);
+        self.expect(tokens, YamlToken::DotDot)?;
+        self.tracer
+            .info(&format!("Parse mapping entry {} at indent {}", key, indent));
+        self.parse_block_value(indent, tokens)
+    }
 
-        // FIXME: this can be also a script
-        // in case of literal we need to consume the space, because
-        // there is no scope
-        self.consume_space_if_exit(tokens);
+    /// Parse the key of a mapping entry, that can be any scalar,
+    /// with the properties in front of it, e.g: `&name name: Mark`.
+    fn parse_key(&mut self, tokens: &mut Lookahead, expected: &str) -> Result<(), YamlParserError> {
+        let properties = self.parse_properties(NodeProperties::default(), tokens)?;
         match self.take(tokens) {
-            // Literal values
-            YamlToken::IntVal(_)
+            YamlToken::Identifier(_)
+            | YamlToken::IntVal(_)
             | YamlToken::StringVal(_)
             | YamlToken::FloatVal(_)
-            | YamlToken::BoolVal(_) => {
-                let yaml_val = self.parse_literal_val(tokens);
-                YamlObject::Mapping(ref_id.to_string(), Box::new(yaml_val), false)
-            }
-            _ => panic!(
-                "Invalid mapping format, encounter token {:?} and parse state: {:?}\n{:?}",
-                self.take(tokens),
-                self,
-                tokens
-            ),
+            | YamlToken::BoolVal(_)
+            | YamlToken::NullVal => self.parse_literal_val(properties, tokens),
+            _ => Err(self.unexpected(tokens, Some(expected))),
         }
     }
 
-    fn open_scope_if_needed(&mut self, tokens: &Vec<YamlToken>) -> bool {
-        let current_scope = self.current_scope_size.unwrap_or(0) as u32;
-        let space_in_front = self.count_spaces(tokens).unwrap_or(0) as u32;
-        if current_scope != 0 && space_in_front < (current_scope / 2) {
-            return false;
+    /// Parse the value of a block mapping entry, that can be on the same
+    /// line of the key, or a block node more indented than the key, e.g:
+    ///
+    /// mark: {hr: 65, avg: 0.278}
+    /// players:
+    ///   - Mark McGwire
+    ///
+    /// The sequence can also have the same indentation of the key.
+    ///
+    /// The comment on the same line of the key is emitted
+    /// after the value.
+    fn parse_block_value(
+        &mut self,
+        indent: usize,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        self.skip_inline_spaces(tokens);
+        let properties = self.parse_properties(NodeProperties::default(), tokens)?;
+        if self.is_inline_value(tokens) {
+            self.states.push(State::TrailingComment);
+            return self.parse_inline_node(properties, tokens, "a value");
+        }
+        let trailing = self.parse_trailing_comment(tokens);
+        self.states.push(State::Trailing(trailing));
+        self.skip_blanks(indent + 1, tokens);
+        // the comments before a sequence with the same indentation
+        // of the key are part of the sequence
+        if self.next_node_indent(tokens) == indent
+            && self.next_node(tokens).map(|token| &token.kind) == Some(&YamlToken::Dash)
+        {
+            self.skip_blanks(indent, tokens);
+        }
+        let next_indent = self.indent(tokens);
+        match self.take(tokens) {
+            // the comments before a less indented node are left
+            // to the parent collection
+            YamlToken::Pount(_) => self.null_node(properties, tokens),
+            _ if self.is_block_end(tokens) => self.null_node(properties, tokens),
+            _ if next_indent > indent => self.parse_block_node(next_indent, properties, tokens),
+            YamlToken::Dash if next_indent == indent => {
+                self.parse_block_sequence(indent, properties, tokens)
+            }
+            // the entry does not have a value
+            _ => self.null_node(properties, tokens),
         }
+    }
 
-        self.open_scope(tokens).unwrap();
-        return true;
+    /// Parse the block node that start at the beginning of a line
+    /// with the indentation given in input, with the properties
+    /// parsed before it.
+    fn parse_block_node(
+        &mut self,
+        indent: usize,
+        properties: NodeProperties,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        let pos = tokens.pos();
+        // the properties in front of an implicit key are the ones
+        // of the key, and not of the mapping
+        let properties = if self.is_key(tokens) {
+            properties
+        } else {
+            self.parse_properties(properties, tokens)?
+        };
+        // the properties can be alone on the line, e.g: `--- !model`,
+        // and the node starts on the next line
+        let indent = if pos != tokens.pos() && !self.is_inline_value(tokens) {
+            self.skip_blanks(0, tokens);
+            self.indent(tokens)
+        } else {
+            indent
+        };
+        match self.take(tokens) {
+            YamlToken::Dash => self.parse_block_sequence(indent, properties, tokens),
+            _ if self.is_key(tokens) => self.parse_block_mapping(indent, properties, tokens),
+            _ => self.parse_inline_node(properties, tokens, "a value"),
+        }
     }
 
-    /// Check if the token in current position is a end of document.
-    fn is_end_of_doc(&self, tokens: &Vec<YamlToken>) -> bool {
-        tokens[self.current_pos as usize] == YamlToken::EndDoc
+    /// Parse the start of a block mapping, where the keys have the
+    /// indentation given in input. The first key can follow the `-`
+    /// of a sequence item on the same line, e.g:
+    ///
+    /// name: Mark McGwire
+    /// hr: 65
+    fn parse_block_mapping(
+        &mut self,
+        indent: usize,
+        properties: NodeProperties,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        let start = self.span(tokens);
+        let anchor = self.start_node(
+            tokens,
+            |anchor, tag| EventKind::MappingStart {
+                anchor,
+                tag,
+                flow: false,
+            },
+            properties,
+            start,
+        )?;
+        self.indents.push(indent);
+        self.states.push(State::BlockMapping {
+            indent,
+            first: true,
+            start,
+            anchor,
+        });
+        Ok(())
     }
 
-    fn count_spaces(&mut self, tokens: &Vec<YamlToken>) -> Result<u8, YamlParserError> {
-        let token = self.take(tokens);
-        if token != YamlToken::Space {
-            return Ok(0);
-        }
-        let mut scope_size: u8 = 0;
-        let mut pos = self.current_pos as usize;
-        while tokens.len() > pos && tokens[pos] == YamlToken::Space {
-            scope_size = scope_size + 1;
-            pos += 1;
+    /// Parse the next entry of the block mapping, or the end of
+    /// the mapping when the next key is less indented.
+    fn parse_block_mapping_entry(
+        &mut self,
+        indent: usize,
+        first: bool,
+        start: Span,
+        anchor: Option<String>,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        self.skip_blanks(indent, tokens);
+        // the comments left by `skip_blanks` are before a less
+        // indented node, that close the mapping
+        if self.is_block_end(tokens)
+            || matches!(self.take(tokens), YamlToken::Pount(_))
+            || (!first && !self.is_block_item(indent, tokens)?)
+        {
+            self.indents.pop();
+            self.end_node(tokens, EventKind::MappingEnd, anchor, start);
+            return Ok(());
         }
-        Ok(scope_size)
+        self.states.push(State::BlockMapping {
+            indent,
+            first: false,
+            start,
+            anchor,
+        });
+        self.parse_mapping_entry(indent, tokens)
     }
 
-    /// Start a new scope from the token that can open a scope.
+    /// Parse the start of a block sequence where the `-` of the
+    /// items have the indentation given in input, e.g:
     ///
-    /// N.B We check the indentation size from the first number of space
-    /// that we found in the stream, so in this way we make sure that the
-    /// document is well format.
-    fn open_scope(&mut self, tokens: &Vec<YamlToken>) -> Result<YamlToken, YamlParserError> {
-        let scope_size = self.count_spaces(tokens).unwrap_or(0) as u32;
-        self.tracer.info(&format!(
-            "Actual scope size: {}",
-            self.current_scope_size.unwrap_or(0)
-        ));
-        self.tracer.info(&format!("new scope: {}", scope_size));
-        match self.current_scope_size {
-            Some(scope_dim) => {
-                self.current_scope_size = Some(scope_size);
-                Ok(self.take(tokens))
+    /// - Mark McGwire
+    /// - name: Sammy Sosa
+    ///   hr: 63
+    /// - - nested
+    ///   - sequence
+    fn parse_block_sequence(
+        &mut self,
+        indent: usize,
+        properties: NodeProperties,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        self.tracer.info("******* Read the Yaml sequence *******");
+        let start = self.span(tokens);
+        let anchor = self.start_node(
+            tokens,
+            |anchor, tag| EventKind::SequenceStart {
+                anchor,
+                tag,
+                flow: false,
+            },
+            properties,
+            start,
+        )?;
+        self.indents.push(indent);
+        self.states.push(State::BlockSequence {
+            indent,
+            first: true,
+            start,
+            anchor,
+        });
+        Ok(())
+    }
+
+    /// Parse the next item of the block sequence, or the end of
+    /// the sequence when the next token is not a `-` with the
+    /// same indentation.
+    fn parse_block_sequence_item(
+        &mut self,
+        indent: usize,
+        first: bool,
+        start: Span,
+        anchor: Option<String>,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        self.skip_blanks(indent, tokens);
+        // a key with the same indentation close a sequence that
+        // is the value of a mapping entry
+        if self.is_block_end(tokens)
+            || (!first && !self.is_block_item(indent, tokens)?)
+            || self.take(tokens) != YamlToken::Dash
+        {
+            self.indents.pop();
+            self.end_node(tokens, EventKind::SequenceEnd, anchor, start);
+            return Ok(());
+        }
+        self.states.push(State::BlockSequence {
+            indent,
+            first: false,
+            start,
+            anchor,
+        });
+        self.flush_comments();
+        self.consume(tokens);
+        self.parse_sequence_item(indent, tokens)
+    }
+
+    /// Parse the item of a block sequence after the `-`, where the
+    /// `-` has the indentation given in input. The comment on the
+    /// same line of the `-` is emitted after the item.
+    fn parse_sequence_item(
+        &mut self,
+        indent: usize,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        self.skip_inline_spaces(tokens);
+        let properties = if self.is_key(tokens) {
+            NodeProperties::default()
+        } else {
+            self.parse_properties(NodeProperties::default(), tokens)?
+        };
+        if self.is_inline_value(tokens) {
+            // the compact collections start on the same line of the `-`
+            let item_indent = self.indent(tokens);
+            return match self.take(tokens) {
+                YamlToken::Dash => self.parse_block_sequence(item_indent, properties, tokens),
+                _ if self.is_key(tokens) => {
+                    self.parse_block_mapping(item_indent, properties, tokens)
+                }
+                _ => {
+                    self.states.push(State::TrailingComment);
+                    self.parse_inline_node(properties, tokens, "a sequence item")
+                }
+            };
+        }
+        let trailing = self.parse_trailing_comment(tokens);
+        self.states.push(State::Trailing(trailing));
+        self.skip_blanks(indent + 1, tokens);
+        let next_indent = self.indent(tokens);
+        match self.take(tokens) {
+            YamlToken::Pount(_) => self.null_node(properties, tokens),
+            _ if self.is_block_end(tokens) || next_indent <= indent => {
+                self.null_node(properties, tokens)
             }
-            None => {
-                // init indentation size
-                self.indentation_size = scope_size as u16;
-                self.current_scope_size = Some(scope_size);
-                Ok(self.take(tokens))
+            _ if self.is_key(tokens) => self.parse_block_mapping(next_indent, properties, tokens),
+            _ => self.parse_block_node(next_indent, properties, tokens),
+        }
+    }
+
+    /// Parse a node defined on a single line, that can be
+    /// a scalar, a flow collection or an alias.
+    fn parse_inline_node(
+        &mut self,
+        properties: NodeProperties,
+        tokens: &mut Lookahead,
+        expected: &str,
+    ) -> Result<(), YamlParserError> {
+        match self.take(tokens) {
+            // Literal values
+            YamlToken::Identifier(_)
+            | YamlToken::IntVal(_)
+            | YamlToken::StringVal(_)
+            | YamlToken::FloatVal(_)
+            | YamlToken::BoolVal(_)
+            | YamlToken::NullVal
+            | YamlToken::Multiline(_) => self.parse_literal_val(properties, tokens),
+            YamlToken::LeftSquareBrace | YamlToken::LeftCurlyBrace => {
+                self.parse_flow_collection(properties, tokens)
             }
+            YamlToken::Alias(_) => self.parse_alias(properties, tokens),
+            _ => Err(self.unexpected(tokens, Some(expected))),
         }
     }
 
-    fn close_scope(&mut self, tokens: &Vec<YamlToken>) {
-        let spaces = self.count_spaces(tokens).unwrap() as u32;
-        if spaces < self.current_scope_size.unwrap() {
-            // TODO: we should divided the space / 2 and not trust the tokens
-            self.current_scope_size = Some(spaces);
-        } else {
-            self.tracer
-                .info(&"You should not close the scope here".to_string());
+    /// Check if the current token is the next item of the block
+    /// collection with the indentation given in input, and return
+    /// false if the token close the collection.
+    fn is_block_item(
+        &self,
+        indent: usize,
+        tokens: &mut Lookahead,
+    ) -> Result<bool, YamlParserError> {
+        if !self.is_line_start(tokens) {
+            return Err(self.unexpected(tokens, Some("a new line")));
         }
+        let current = self.indent(tokens);
+        if current < indent {
+            // the token must close one of the parent collections
+            if !self.indents.contains(&current) {
+                let levels: Vec<String> = self
+                    .indents
+                    .iter()
+                    .filter(|level| **level < indent)
+                    .map(|level| level.to_string())
+                    .collect();
+                return Err(self.error(
+                    tokens,
+                    YamlParserErrorKind::BadIndentation,
+                    Some(&format!("{} spaces", levels.join(" or "))),
+                ));
+            }
+            return Ok(false);
+        }
+        self.check_indent(indent, tokens)?;
+        Ok(true)
     }
 
-    fn consume_or_close_scope(&mut self, tokens: &Vec<YamlToken>) {
-        // Fallback case, in this block we check if we are still in the current scope
-        if self.current_scope(tokens) {
-            self.consume_scope(tokens);
-        } else {
-            self.close_scope(tokens);
+    /// Check that the current token has the indentation given in input.
+    fn check_indent(&self, indent: usize, tokens: &mut Lookahead) -> Result<(), YamlParserError> {
+        if !self.is_line_start(tokens) {
+            return Err(self.unexpected(tokens, Some("a new line")));
         }
+        if self.indent(tokens) != indent {
+            return Err(self.error(
+                tokens,
+                YamlParserErrorKind::BadIndentation,
+                Some(&format!("{} spaces", indent)),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Indentation of the current token, that is the
+    /// column where the token start.
+    fn indent(&self, tokens: &mut Lookahead) -> usize {
+        self.span(tokens).column.saturating_sub(1)
     }
 
-    fn consume_scope(&mut self, tokens: &Vec<YamlToken>) {
-        self.tracer.info(&"consume_scope: start".to_string());
-        match self.current_scope_size {
-            Some(size) => {
-                for _ in 0..size {
-                    self.tracer.debug(&format!("Parse status: {:?}", self));
-                    assert_eq!(YamlToken::Space, self.next(tokens));
-                    self.tracer.info(&format!(
-                        "Next token after space consumed: {:?}",
-                        self.take(tokens)
-                    ));
+    /// Check if the current token is the first one of its line.
+    fn is_line_start(&self, tokens: &mut Lookahead) -> bool {
+        let line = self.span(tokens).line;
+        tokens.last_content().is_none_or(|span| span.line < line)
+    }
+
+    /// Check if the current token is a value on the same line
+    /// of the last token consumed.
+    fn is_inline_value(&self, tokens: &mut Lookahead) -> bool {
+        !matches!(self.take(tokens), YamlToken::Pount(_) | YamlToken::EOF)
+            && !self.is_line_start(tokens)
+    }
+
+    /// Check if the current token close all the
+    /// block collections of the document.
+    fn is_block_end(&self, tokens: &mut Lookahead) -> bool {
+        matches!(
+            self.take(tokens),
+            YamlToken::EOF | YamlToken::StartDoc | YamlToken::EndDoc | YamlToken::Directive(_)
+        )
+    }
+
+    /// Skip the spaces and the comments before the next node with
+    /// the indentation given in input. The comments before a less
+    /// indented node are left to the parent collection.
+    ///
+    /// The comments skipped are attached to the next entry or item.
+    /// All the comments before a node have the same next node, so
+    /// the run of comments is skipped after a single lookahead.
+    fn skip_blanks(&mut self, indent: usize, tokens: &mut Lookahead) {
+        loop {
+            match self.take(tokens) {
+                YamlToken::Space | YamlToken::TabSpace => self.consume(tokens),
+                YamlToken::Pount(_) if self.next_node_indent(tokens) >= indent => {
+                    self.skip_comments(tokens)
                 }
+                _ => return,
             }
-            None => panic!("No scope found"),
         }
-        self.tracer.info(&"consume_scope: end".to_string());
     }
 
-    fn consume_space_if_exit(&mut self, tokens: &Vec<YamlToken>) {
-        if self.take(tokens) == YamlToken::Space {
-            self.consume(tokens);
+    /// Skip the spaces and parse the comments until the next node.
+    fn skip_comments(&mut self, tokens: &mut Lookahead) {
+        loop {
+            match self.take(tokens) {
+                YamlToken::Space | YamlToken::TabSpace => self.consume(tokens),
+                YamlToken::Pount(_) => self.parse_comment(tokens),
+                _ => return,
+            }
         }
     }
 
-    fn current_scope(&mut self, tokens: &Vec<YamlToken>) -> bool {
-        match self.current_scope_size {
-            Some(spaces) => {
-                if let Ok(current_size) = self.count_spaces(tokens) {
-                    current_size as u32 == spaces
-                } else {
-                    false
-                }
+    /// Parse the comment on the same line of the last token
+    /// consumed, e.g: `name: string # name of the user`.
+    fn parse_trailing_comment(&mut self, tokens: &mut Lookahead) -> Option<(String, Span)> {
+        self.skip_inline_spaces(tokens);
+        match self.take(tokens) {
+            YamlToken::Pount(content) if !self.is_line_start(tokens) => {
+                let span = self.span(tokens);
+                self.consume(tokens);
+                Some((content, span))
             }
-            None => true,
+            _ => None,
         }
     }
 
-    /// wrapping the way of the compiler to build the error
-    fn return_error(&self, cause: &str) -> YamlParserError {
-        YamlParserError {
-            cause: cause.to_string(),
+    /// Emit the comment on the same line of the entry or
+    /// the item just parsed.
+    fn emit_trailing(&mut self, trailing: Option<(String, Span)>) {
+        if let Some((content, span)) = trailing {
+            let kind = EventKind::Comment {
+                content,
+                trailing: true,
+            };
+            self.emit(kind, span);
         }
     }
 
-    /// Parse the document comment and store in a new comment node
-    fn parse_comment(&mut self, tokens: &Vec<YamlToken>) {
+    /// Emit the comments skipped until now, that annotate the entry or
+    /// the item that follow them, otherwise they are part of the document.
+    fn flush_comments(&mut self) {
+        for (content, span) in mem::take(&mut self.comments) {
+            let kind = EventKind::Comment {
+                content,
+                trailing: false,
+            };
+            self.emit(kind, span);
+        }
+    }
+
+    /// Indentation of the next node after the spaces and the comments.
+    fn next_node_indent(&self, tokens: &mut Lookahead) -> usize {
+        self.next_node(tokens)
+            .filter(|token| token.kind != YamlToken::EOF)
+            .map(|token| token.span.column.saturating_sub(1))
+            .unwrap_or_default()
+    }
+
+    /// First token of the next node after the spaces and the comments.
+    fn next_node<'a>(&self, tokens: &'a mut Lookahead) -> Option<&'a Token> {
+        let mut distance = 0;
+        while tokens
+            .peek(distance)
+            .is_some_and(|token| is_blank(&token.kind) || matches!(token.kind, YamlToken::Pount(_)))
+        {
+            distance += 1;
+        }
+        tokens.peek(distance)
+    }
+
+    /// Skip the spaces, including the indentation.
+    fn skip_spaces(&mut self, tokens: &mut Lookahead) {
+        while is_blank(&self.take(tokens)) {
+            self.consume(tokens);
+        }
+    }
+
+    /// Null scalar of an empty value, at the position
+    /// of the last token consumed.
+    fn null_node(
+        &mut self,
+        properties: NodeProperties,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        let span = self.last_span(tokens);
+        self.count_node(tokens, span)?;
+        self.scalar(properties, YamlObject::Null, ScalarStyle::Plain, span);
+        Ok(())
+    }
+
+    /// Parse the comment line and keep it until the next entry or
+    /// item, otherwise it is emitted as a comment of the document.
+    fn parse_comment(&mut self, tokens: &mut Lookahead) {
         match self.take(tokens) {
             YamlToken::Pount(content) => {
-                let comment = YamlObject::Comment(content.to_string());
-                self.add_to_ir(comment);
+                self.comments.push((content, self.span(tokens)));
                 self.consume(tokens);
             }
             _ => self.skip(),
         }
     }
 
-    /// parse Yaml mapping key: value or other type of mapping like:
-    /// ----- Scalar to Sequence ----
-    /// american:
-    // - Boston Red Sox
-    // - Detroit Tigers
-    // - New York Yankees
-    /// --------- Sequence of mapping ---------
-    /// -
-    ///   name: Mark McGwire
-    ///   hr:   65
-    ///   avg:  0.278
-    /// --------- Sequence of Sequence ---------
-    /// - [name        , hr, avg  ]
-    /// --------- Mapping of Mappings ------
+    fn parse_literal_val(
+        &mut self,
+        properties: NodeProperties,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        let span = self.span(tokens);
+        let (literal, style) = match self.take(tokens) {
+            YamlToken::IntVal(val) => (YamlObject::Int(val), ScalarStyle::Plain),
+            YamlToken::FloatVal(val) => (YamlObject::Float(val), ScalarStyle::Plain),
+            YamlToken::Identifier(val) => (YamlObject::Str(val), ScalarStyle::Plain),
+            YamlToken::StringVal(val) => (YamlObject::Str(val), ScalarStyle::Quoted),
+            YamlToken::Multiline(val) => (YamlObject::Str(val), ScalarStyle::Block),
+            YamlToken::BoolVal(val) => (YamlObject::Bool(val), ScalarStyle::Plain),
+            YamlToken::NullVal => (YamlObject::Null, ScalarStyle::Plain),
+            _ => return Err(self.unexpected(tokens, Some("a literal value"))),
+        };
+        self.count_node(tokens, span)?;
+        self.consume(tokens);
+        self.scalar(properties, literal, style, span);
+        Ok(())
+    }
+
+    /// Parse the start of a flow sequence, that can be also
+    /// nested and defined on more lines, e.g:
+    ///
+    /// tags: [name, [hr, avg], 0.278]
+    fn parse_flow_sequence(
+        &mut self,
+        properties: NodeProperties,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        let start = self.span(tokens);
+        self.expect(tokens, YamlToken::LeftSquareBrace)?;
+        let anchor = self.start_node(
+            tokens,
+            |anchor, tag| EventKind::SequenceStart {
+                anchor,
+                tag,
+                flow: true,
+            },
+            properties,
+            start,
+        )?;
+        self.states.push(State::FlowSequence { start, anchor });
+        Ok(())
+    }
+
+    /// Parse the next item of the flow sequence, or the `]`
+    /// that close the sequence.
+    fn parse_flow_sequence_item(
+        &mut self,
+        start: Span,
+        anchor: Option<String>,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        self.skip_flow_spaces(tokens);
+        if self.take(tokens) == YamlToken::RightSquareBrace {
+            self.consume(tokens);
+            self.end_node(tokens, EventKind::SequenceEnd, anchor, start);
+            return Ok(());
+        }
+        self.states.push(State::FlowSequenceNext { start, anchor });
+        self.parse_flow_node(tokens, "a flow sequence item or `]`")
+    }
+
+    /// Skip the spaces and the comments inside a flow collection,
+    /// where the indentation does not matter.
+    fn skip_flow_spaces(&mut self, tokens: &mut Lookahead) {
+        while matches!(
+            self.take(tokens),
+            YamlToken::Space | YamlToken::TabSpace | YamlToken::Pount(_)
+        ) {
+            self.consume(tokens);
+        }
+    }
+
+    /// Parse the start of a flow mapping, that is stored as
+    /// a mapping with the braced flag set, e.g:
+    ///
     /// Mark McGwire: {hr: 65, avg: 0.278}
-    fn parse_mapping(&mut self, tag: &str, tokens: &Vec<YamlToken>) -> YamlObject {
-        let token = self.take(tokens);
-        self.tracer
-            .info(&format!("Parse mapping: start with token {:?}", token));
-        self.tracer.info(&format!("Parser State {:?}", self));
-        let item = match token {
-            YamlToken::Dash => self.parse_scalar_to_sequence(tokens),
-            YamlToken::LeftSquareBrace => self.parse_sequence_of_sequence(tokens),
-            YamlToken::LeftCurlyBrace => self.parse_mapping_of_mapping(tokens),
-            YamlToken::Identifier(ref sub_tag) => {
-                self.consume(tokens);
-                let yaml_identifier = self.parse_identifier(sub_tag.as_str(), tokens);
-                YamlObject::Mapping(sub_tag.to_string(), Box::new(yaml_identifier), false)
+    fn parse_flow_mapping(
+        &mut self,
+        properties: NodeProperties,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        let start = self.span(tokens);
+        self.expect(tokens, YamlToken::LeftCurlyBrace)?;
+        let anchor = self.start_node(
+            tokens,
+            |anchor, tag| EventKind::MappingStart {
+                anchor,
+                tag,
+                flow: true,
+            },
+            properties,
+            start,
+        )?;
+        self.states.push(State::FlowMapping { start, anchor });
+        Ok(())
+    }
+
+    /// Parse the next entry of the flow mapping, or the `}`
+    /// that close the mapping.
+    fn parse_flow_mapping_entry(
+        &mut self,
+        start: Span,
+        anchor: Option<String>,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        self.skip_flow_spaces(tokens);
+        if self.take(tokens) == YamlToken::RightCurlyBrace {
+            self.consume(tokens);
+            self.end_node(tokens, EventKind::MappingEnd, anchor, start);
+            return Ok(());
+        }
+        self.parse_key(tokens, "a flow mapping key or `}`")?;
+        self.skip_flow_spaces(tokens);
+        self.states.push(State::FlowMappingNext { start, anchor });
+        // a key without value, e.g: `{key, other: 1}`, has a null value
+        if self.take(tokens) == YamlToken::DotDot {
+            self.consume(tokens);
+            self.skip_flow_spaces(tokens);
+            self.parse_flow_node(tokens, "a flow mapping value")
+        } else {
+            self.null_node(NodeProperties::default(), tokens)
+        }
+    }
+
+    /// Parse a flow sequence or a flow mapping.
+    fn parse_flow_collection(
+        &mut self,
+        properties: NodeProperties,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        match self.take(tokens) {
+            YamlToken::LeftCurlyBrace => self.parse_flow_mapping(properties, tokens),
+            _ => self.parse_flow_sequence(properties, tokens),
+        }
+    }
+
+    /// Parse a node inside a flow collection, where the plain
+    /// identifiers are strings.
+    fn parse_flow_node(
+        &mut self,
+        tokens: &mut Lookahead,
+        expected: &str,
+    ) -> Result<(), YamlParserError> {
+        let properties = self.parse_properties(NodeProperties::default(), tokens)?;
+        match self.take(tokens) {
+            YamlToken::LeftSquareBrace | YamlToken::LeftCurlyBrace => {
+                self.parse_flow_collection(properties, tokens)
             }
-            // Literal values
-            YamlToken::IntVal(_)
+            YamlToken::Identifier(_)
+            | YamlToken::IntVal(_)
             | YamlToken::StringVal(_)
             | YamlToken::FloatVal(_)
-            | YamlToken::BoolVal(_) => {
-                let yaml_val = self.parse_literal_val(tokens);
-                YamlObject::Mapping(tag.to_string(), Box::new(yaml_val), false)
+            | YamlToken::BoolVal(_)
+            | YamlToken::NullVal => self.parse_literal_val(properties, tokens),
+            YamlToken::Alias(_) => self.parse_alias(properties, tokens),
+            _ => Err(self.unexpected(tokens, Some(expected))),
+        }
+    }
+
+    /// Parse the properties in front of a node, that are the
+    /// anchor, e.g: `&audit`, and the tag, e.g: `!!str`, in any order.
+    /// The properties given in input are the ones already parsed
+    /// for the same node, e.g: on the line before the node.
+    ///
+    /// The anchor is pending until the node is parsed, so an
+    /// alias to it inside the node is a cycle.
+    fn parse_properties(
+        &mut self,
+        mut properties: NodeProperties,
+        tokens: &mut Lookahead,
+    ) -> Result<NodeProperties, YamlParserError> {
+        loop {
+            match self.take(tokens) {
+                YamlToken::Anchor(name) if properties.anchor.is_none() => {
+                    let expanded = self.nodes + self.aliased;
+                    self.pending_anchors.push((name.clone(), expanded));
+                    properties.anchor = Some(name);
+                }
+                YamlToken::Tag(tag) if properties.tag.is_none() => {
+                    let tag = self.expand_tag(tokens, &tag)?;
+                    properties.tag = Some((tag, self.span(tokens)));
+                }
+                _ => return Ok(properties),
             }
-            _ => panic!(
-                "Invalid mapping format, encounter token {:?} and parse state: {:?}",
-                token, self
-            ),
+            self.consume(tokens);
+            self.skip_inline_spaces(tokens);
+        }
+    }
+
+    /// Expand the tag shorthand with the handle declared by the `%TAG`
+    /// directives of the document, e.g: `!!str` -> `tag:yaml.org,2002:str`.
+    ///
+    /// The local tags like `!ref` are not expanded when the `!` handle
+    /// is not declared.
+    fn expand_tag(&self, tokens: &mut Lookahead, tag: &str) -> Result<String, YamlParserError> {
+        if let Some(verbatim) = tag.strip_prefix("!<").and_then(|tag| tag.strip_suffix('>')) {
+            return Ok(verbatim.to_string());
+        }
+        // the handle can be `!`, `!!` or `!name!`
+        let (handle, suffix) = match tag[1..].find('!') {
+            Some(end) => tag.split_at(end + 2),
+            None => tag.split_at(1),
         };
-        YamlObject::Mapping(tag.to_string(), Box::new(item), false)
+        match self.tag_handles.get(handle) {
+            Some(prefix) => Ok(format!("{prefix}{suffix}")),
+            None if handle == "!!" => Ok(format!("{YAML_TAG_PREFIX}{suffix}")),
+            None if handle == "!" => Ok(tag.to_string()),
+            None => Err(self.error(
+                tokens,
+                YamlParserErrorKind::InvalidTag,
+                Some("a handle declared with `%TAG`"),
+            )),
+        }
+    }
+
+    /// Define the anchor of the node just parsed, so the aliases
+    /// after it can point to the node, with the number of nodes
+    /// that the node contains, including the ones of its aliases.
+    fn define_anchor(&mut self, anchor: Option<String>) {
+        let Some(name) = anchor else {
+            return;
+        };
+        let start = self
+            .pending_anchors
+            .iter()
+            .rev()
+            .find(|(pending, _)| *pending == name)
+            .map(|(_, start)| *start)
+            .unwrap_or_default();
+        self.pending_anchors.retain(|(pending, _)| *pending != name);
+        // an anchor can be redefined, and the following aliases
+        // point to the last node defined.
+        let size = (self.nodes + self.aliased).saturating_sub(start);
+        self.anchors.insert(name, size);
     }
 
-    fn parse_literal_val(&mut self, tokens: &Vec<YamlToken>) -> YamlObject {
-        let literal = match self.take(tokens) {
-            YamlToken::IntVal(val) => YamlObject::Int(val),
-            YamlToken::FloatVal(val) => YamlObject::Float(val),
-            YamlToken::StringVal(val) => YamlObject::Str(val),
-            YamlToken::BoolVal(val) => YamlObject::Bool(val),
-            YamlToken::NullVal => YamlObject::Null,
-            _ => panic!("Not a literal value {:?}. \n{:?}", self.take(tokens), self),
+    /// Parse an alias, e.g: `*audit`, to the node with the same
+    /// anchor defined before it.
+    fn parse_alias(
+        &mut self,
+        properties: NodeProperties,
+        tokens: &mut Lookahead,
+    ) -> Result<(), YamlParserError> {
+        let start = self.span(tokens);
+        let YamlToken::Alias(name) = self.take(tokens) else {
+            return Err(self.unexpected(tokens, Some("an alias")));
+        };
+        if properties.anchor.is_some() || properties.tag.is_some() {
+            return Err(self.unexpected(tokens, Some("a node, an alias can not have properties")));
+        }
+        if self
+            .pending_anchors
+            .iter()
+            .any(|(pending, _)| *pending == name)
+        {
+            return Err(self.error(tokens, YamlParserErrorKind::RecursiveAlias, None));
+        }
+        let Some(size) = self.anchors.get(&name).copied() else {
+            return Err(self.error(tokens, YamlParserErrorKind::UndefinedAlias, None));
         };
+        // each alias expands all the nodes of the anchor, so a small
+        // content can expand a huge number of nodes, e.g: billion laughs
+        self.aliased = self.aliased.saturating_add(size);
+        if self.aliased > self.max_alias_expansion {
+            return Err(self.error(
+                tokens,
+                YamlParserErrorKind::AliasLimitExceeded,
+                Some(&format!(
+                    "at most {} nodes expanded by the aliases",
+                    self.max_alias_expansion
+                )),
+            ));
+        }
         self.consume(tokens);
-        literal
+        self.emit(EventKind::Alias(name), start);
+        Ok(())
     }
 
-    /// parse Yaml mapping key: value or other type of mapping like:
-    /// ----- Scalar to Sequence ----
-    /// american:
-    /// - Boston Red Sox
-    /// - Detroit Tigers
-    /// - New York Yankees
+    /// Skip the spaces on the line of the last token consumed,
+    /// without consume the indentation of the next line.
+    fn skip_inline_spaces(&mut self, tokens: &mut Lookahead) {
+        let line = self.last_span(tokens).line;
+        while is_blank(&self.take(tokens)) && self.span(tokens).line == line {
+            self.consume(tokens);
+        }
+    }
+
+    /// Add the event to the events produced by the current state.
+    fn emit(&mut self, kind: EventKind, span: Span) {
+        self.events.push_back(Event::new(kind, span));
+    }
+
+    /// Emit the scalar with the properties parsed in front of it.
+    fn scalar(
+        &mut self,
+        properties: NodeProperties,
+        value: YamlObject,
+        style: ScalarStyle,
+        span: Span,
+    ) {
+        let kind = EventKind::Scalar {
+            value,
+            style,
+            anchor: properties.anchor.clone(),
+            tag: properties.tag,
+        };
+        self.emit(kind, span);
+        self.define_anchor(properties.anchor);
+    }
+
+    /// Emit the start of a collection with the properties parsed in
+    /// front of it, and return the anchor that is defined at the end.
     ///
-    /// In this case there are situation where we need to parse the indentation
-    fn parse_scalar_to_sequence(&mut self, tokens: &Vec<YamlToken>) -> YamlObject {
-        self.tracer
-            .info(&"******* Read the Yaml sequence *******".to_string());
-        let mut yaml_seq = vec![];
-        while self.take(tokens) == YamlToken::Dash {
-            assert_eq!(self.take(tokens), YamlToken::Dash);
-            self.tracer.info(&format!(
-                "parse scalar to sequence: Start with token {:?}",
-                self.take(tokens)
+    /// The collections nested more than the max depth are an error.
+    fn start_node<F>(
+        &mut self,
+        tokens: &mut Lookahead,
+        kind: F,
+        properties: NodeProperties,
+        start: Span,
+    ) -> Result<Option<String>, YamlParserError>
+    where
+        F: FnOnce(Option<String>, Option<(String, Span)>) -> EventKind,
+    {
+        if self.depth >= self.max_depth {
+            return Err(YamlParserError::new(
+                YamlParserErrorKind::DepthLimitExceeded,
+                Some(&format!("at most {} nested collections", self.max_depth)),
+                self.take(tokens),
+                start,
+            ));
+        }
+        self.count_node(tokens, start)?;
+        self.depth += 1;
+        let anchor = properties.anchor.clone();
+        self.emit(kind(properties.anchor, properties.tag), start);
+        Ok(anchor)
+    }
+
+    /// Count the node that start at the span given in input, where
+    /// the nodes more than the max number of nodes are an error.
+    fn count_node(&mut self, tokens: &mut Lookahead, start: Span) -> Result<(), YamlParserError> {
+        self.nodes += 1;
+        if self.nodes > self.max_nodes {
+            return Err(YamlParserError::new(
+                YamlParserErrorKind::NodeLimitExceeded,
+                Some(&format!("at most {} nodes", self.max_nodes)),
+                self.take(tokens),
+                start,
             ));
-            self.consume(tokens); //consume dash
-                                  // TODO: this need to be a must, we need to change it
-            self.consume_space_if_exit(tokens);
-            match self.take(tokens) {
-                YamlToken::IntVal(_) | YamlToken::StringVal(_) | YamlToken::FloatVal(_) => {
-                    let yaml_literal = self.parse_literal_val(tokens);
-                    yaml_seq.push(yaml_literal);
-                    self.tracer.info(&format!(
-                        "parse scalar to sequence: token after literlar: {:?}",
-                        self.take(tokens)
-                    ));
-                    self.consume_or_close_scope(tokens);
-                }
-                YamlToken::Identifier(name) => {
-                    self.consume(tokens);
-                    let yaml_identifier = self.parse_identifier(name.as_str(), tokens);
-                    self.tracer.info(&format!(
-                        "Identifier found {:?}, position at token {:?}",
-                        yaml_identifier,
-                        self.take(tokens)
-                    ));
-                    yaml_seq.push(yaml_identifier);
-                    // if we are in the same scope parser it
-                    self.consume_or_close_scope(tokens);
-                }
-                _ => panic!("Unsupported: {:?}", self.take(tokens)),
-            }
         }
-        YamlObject::List(yaml_seq)
+        Ok(())
     }
 
-    fn parse_sequence_of_sequence(&mut self, tokens: &Vec<YamlToken>) -> YamlObject {
-        YamlObject::Fake
+    /// Emit the end of the node that start at the span given in input,
+    /// with the span of the last token consumed, ignoring the
+    /// indentation consumed after the node.
+    fn end_node(
+        &mut self,
+        tokens: &mut Lookahead,
+        kind: EventKind,
+        anchor: Option<String>,
+        start: Span,
+    ) {
+        let end = tokens.last_node().unwrap_or(start);
+        if matches!(kind, EventKind::MappingEnd | EventKind::SequenceEnd) {
+            self.depth -= 1;
+        }
+        self.emit(kind, end);
+        self.define_anchor(anchor);
+    }
+
+    /// Take the element at the current position of the stream,
+    /// or the EOF if the stream is already consumed.
+    fn take(&self, tokens: &mut Lookahead) -> YamlToken {
+        match tokens.peek(0) {
+            Some(token) => token.kind.clone(),
+            None => YamlToken::EOF,
+        }
     }
 
-    fn parse_mapping_of_mapping(&mut self, tokens: &Vec<YamlToken>) -> YamlObject {
-        YamlObject::Fake
+    /// Span of the element at the current position of the stream,
+    /// or the span of the last token if the stream is already consumed.
+    fn span(&self, tokens: &mut Lookahead) -> Span {
+        tokens.span()
     }
 
-    /// Add the yaml node to the list of Yaml node
-    /// collected from the parser
-    // TODO: find the error here generated by the & of the yaml_node
-    // it is related to the copy but I don't know much
-    fn add_to_ir(&mut self, yaml_node: YamlObject) {
-        self.ir.push(yaml_node);
+    /// Check if the token in the current position is a key, that
+    /// is followed by `:` on the same line. The key can have the
+    /// properties in front of it, e.g: `&name name: Mark`.
+    fn is_key(&self, tokens: &mut Lookahead) -> bool {
+        let line = self.span(tokens).line;
+        let mut distance = 0;
+        while tokens.peek(distance).is_some_and(|token| {
+            matches!(token.kind, YamlToken::Anchor(_) | YamlToken::Tag(_))
+                || (distance > 0 && is_blank(&token.kind))
+        }) {
+            distance += 1;
+        }
+        if tokens
+            .peek(distance)
+            .is_none_or(|key| key.span.line != line)
+        {
+            return false;
+        }
+        distance += 1;
+        while tokens
+            .peek(distance)
+            .is_some_and(|token| is_blank(&token.kind))
+        {
+            distance += 1;
+        }
+        tokens
+            .peek(distance)
+            .is_some_and(|token| token.kind == YamlToken::DotDot)
     }
 
-    /// Check for scope definition in the tokens stream
-    /// and return a flag with the scope that the parser found
-    fn parse_scope(&self, tokens: &Vec<YamlToken>) -> YamlScope {
-        YamlScope::InvalidScope("TODO: implement this part".to_string())
+    /// Span of the last token consumed.
+    fn last_span(&self, tokens: &mut Lookahead) -> Span {
+        match tokens.last() {
+            Some(span) => span,
+            None => self.span(tokens),
+        }
     }
 
-    /// Take the next element in the stream, and increase the current position
-    /// of it.
-    fn next<'a>(&'a mut self, tokens: &'a Vec<YamlToken>) -> YamlToken {
-        self.current_pos += 1;
-        tokens[(self.current_pos - 1) as usize].clone()
+    /// Consume the token in the current position if it is the
+    /// one expected, otherwise return an error.
+    fn expect(
+        &mut self,
+        tokens: &mut Lookahead,
+        expected: YamlToken,
+    ) -> Result<(), YamlParserError> {
+        if self.take(tokens) != expected {
+            return Err(self.unexpected(tokens, Some(&expected.to_string())));
+        }
+        self.consume(tokens);
+        Ok(())
     }
 
-    /// Take the element at the current position of the stream.
-    fn take(&self, tokens: &Vec<YamlToken>) -> YamlToken {
-        tokens[self.current_pos as usize].clone()
+    /// Build the error that report the token in the current position
+    /// as an unexpected token.
+    fn unexpected(&self, tokens: &mut Lookahead, expected: Option<&str>) -> YamlParserError {
+        let kind = match self.take(tokens) {
+            YamlToken::EOF => YamlParserErrorKind::UnexpectedEof,
+            _ => YamlParserErrorKind::UnexpectedToken,
+        };
+        self.error(tokens, kind, expected)
     }
 
-    fn is_the_end(&self, tokens: &Vec<YamlToken>) -> bool {
-        tokens[self.current_pos as usize] == YamlToken::EOF
+    /// wrapping the way to build a parser error on the
+    /// token in the current position.
+    fn error(
+        &self,
+        tokens: &mut Lookahead,
+        kind: YamlParserErrorKind,
+        expected: Option<&str>,
+    ) -> YamlParserError {
+        YamlParserError::new(kind, expected, self.take(tokens), self.span(tokens))
     }
 
-    fn is_the_biginning(&self) -> bool {
-        self.current_pos == 0
+    fn is_the_end(&self, tokens: &mut Lookahead) -> bool {
+        self.take(tokens) == YamlToken::EOF
     }
 
     /// do nothings, just make the code more readble.
@@ -420,27 +1420,125 @@ impl Parser {
 
     /// consume the token in current position without store
     /// the value.
-    /// it is the same of next, but it just make the code more readble
-    fn consume(&mut self, tokens: &Vec<YamlToken>) {
-        self.next(tokens);
+    fn consume(&mut self, tokens: &mut Lookahead) {
+        tokens.consume();
     }
 }
 
+/// Prefix of the standard tags, e.g: `!!str`
+pub(crate) const YAML_TAG_PREFIX: &str = "tag:yaml.org,2002:";
+
+/// Properties that can be defined in front of a node.
+#[derive(Debug, Default)]
+struct NodeProperties {
+    anchor: Option<String>,
+    /// tag expanded with the span where it is defined
+    tag: Option<(String, Span)>,
+}
+
 /// Common implementation of the Yaml Parser
-impl YamlParser<YamlObject> for Parser {
-    fn parse(&mut self, tokens: &Vec<YamlToken>) -> &Vec<YamlObject> {
-        self.walk(tokens);
-        &self.ir
+impl YamlParser<YamlNode> for Parser {
+    /// Parse the stream of tokens, and return a `YamlObject::Document`
+    /// node for each document found, built from the events of
+    /// the stream.
+    fn parse(&mut self, tokens: &[Token]) -> Result<Vec<YamlNode>, YamlParserError> {
+        match self.parse_tokens(tokens.iter().cloned().map(Ok)) {
+            Ok(documents) => Ok(documents),
+            Err(YamlError::Parser(err)) => Err(err),
+            Err(YamlError::Scanner(err)) => unreachable!("the tokens are already scanned: {err}"),
+        }
     }
 }
-
 #[cfg(test)]
 mod test {
+
+    use std::rc::Rc;
+
     use crate::parser::parser::{Parser, YamlParser};
+    use crate::parser::tokens::{YamlNode, YamlObject};
+    use crate::parser::YamlParserErrorKind;
+    use crate::scanner::tokens::YamlToken;
     use crate::scanner::{scanner::Scanner, YamlScanner};
     use utils::tracer::{DefLogTracer, Tracer};
 
     use indoc::indoc;
+
+    /// Parse the content that contains a single document
+    /// and return the nodes of the document.
+    fn parse_single_document(content: &str) -> Vec<YamlNode> {
+        let tokens = Scanner::new().scan(content).unwrap();
+        let mut documents = Parser::new().parse(&tokens).unwrap();
+        assert_eq!(documents.len(), 1);
+        match documents.remove(0).object {
+            YamlObject::Document(nodes) => nodes,
+            object => panic!("expected a document, found {:?}", object),
+        }
+    }
+
+    /// Parse the content that contains a single document
+    /// and return the root node of the document.
+    fn parse_root(content: &str) -> YamlNode {
+        parse_single_document(content)
+            .into_iter()
+            .find(|node| !matches!(node.object, YamlObject::Comment(_)))
+            .unwrap()
+    }
+
+    /// Items of the sequence node given in input.
+    fn items(node: &YamlNode) -> &[YamlNode] {
+        match &node.resolve().object {
+            YamlObject::List(items) => items,
+            object => panic!("expected a list, found {:?}", object),
+        }
+    }
+
+    /// String keys of the mapping node given in input.
+    fn keys(node: &YamlNode) -> Vec<&str> {
+        node.entries()
+            .unwrap()
+            .iter()
+            .filter_map(|entry| entry.key_str())
+            .collect()
+    }
+
+    /// Shape of the node without the spans, the comments and the
+    /// style of the collections, used to compare the trees of two
+    /// contents that must be parsed in the same way.
+    fn tree(node: &YamlNode) -> String {
+        let shape = match &node.resolve().object {
+            YamlObject::Document(nodes) | YamlObject::List(nodes) => {
+                let nodes: Vec<String> = nodes
+                    .iter()
+                    .filter(|node| !matches!(node.object, YamlObject::Comment(_)))
+                    .map(tree)
+                    .collect();
+                format!("[{}]", nodes.join(", "))
+            }
+            YamlObject::Mapping(entries, _) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|entry| format!("{}: {}", tree(&entry.key), tree(&entry.value)))
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            object => format!("{:?}", object),
+        };
+        match node.tag() {
+            Some(tag) => format!("{tag} {shape}"),
+            None => shape,
+        }
+    }
+
+    /// Parse the two contents and check that they have the same tree.
+    fn assert_same_tree(left: &str, right: &str) {
+        let parse = |content: &str| -> Vec<String> {
+            let tokens = Scanner::new().scan(content).unwrap();
+            let documents = Parser::new().parse(&tokens).unwrap();
+            documents.iter().map(tree).collect()
+        };
+        assert_eq!(parse(left), parse(right));
+    }
+
     #[test]
     fn parse_simple_one() {
         env_logger::init();
@@ -457,9 +1555,9 @@ national:
  - \"Chicago Cubs\"
  - \"Atlanta Braves\"
 "};
-        let tokens = scanner.scan(&simple_yaml);
-        let ir = parser.parse(&tokens);
-        assert!(ir.len() > 0);
+        let tokens = scanner.scan(simple_yaml).unwrap();
+        let ir = parser.parse(&tokens).unwrap();
+        assert!(!ir.is_empty());
     }
 
     #[test]
@@ -483,8 +1581,632 @@ ModelOne:
       - nullable: true
       - reference: true"};
 
-        let tokens = scanner.scan(&simple_yaml);
-        let ir = parser.parse(&tokens);
-        assert!(ir.len() > 0);
+        let tokens = scanner.scan(simple_yaml).unwrap();
+        let ir = parser.parse(&tokens).unwrap();
+        assert!(!ir.is_empty());
+    }
+
+    #[test]
+    fn parse_unexpected_value() {
+        let mut scanner = Scanner::new();
+        let mut parser = Parser::new();
+        let tokens = scanner.scan("ModelOne:\n  PropOne: ]\n").unwrap();
+        let err = parser.parse(&tokens).unwrap_err();
+        assert_eq!(err.kind, YamlParserErrorKind::UnexpectedToken);
+        assert_eq!(err.expected, Some("a value".to_string()));
+        assert_eq!(err.found, YamlToken::RightSquareBrace);
+        assert_eq!((err.span.line, err.span.column), (2, 12));
+        assert_eq!(
+            err.to_string(),
+            "unexpected `]`, expected a value at line 2, column 12"
+        );
+    }
+
+    #[test]
+    fn parse_unexpected_token() {
+        let mut scanner = Scanner::new();
+        let mut parser = Parser::new();
+        let tokens = scanner.scan("ModelOne: 12\n: 13\n").unwrap();
+        let err = parser.parse(&tokens).unwrap_err();
+        assert_eq!(err.kind, YamlParserErrorKind::UnexpectedToken);
+        assert_eq!(err.found, YamlToken::DotDot);
+        assert_eq!((err.span.line, err.span.column), (2, 1));
+    }
+
+    #[test]
+    fn parse_unexpected_eof() {
+        let mut scanner = Scanner::new();
+        let mut parser = Parser::new();
+        let tokens = scanner.scan("ModelOne: {PropOne: int64").unwrap();
+        let err = parser.parse(&tokens).unwrap_err();
+        assert_eq!(err.kind, YamlParserErrorKind::UnexpectedEof);
+        assert_eq!(err.found, YamlToken::EOF);
+    }
+
+    #[test]
+    fn parse_node_spans() {
+        let content = "# models\nModelOne:\n  - PropOne: \"int64\"\n";
+        let ir = parse_single_document(content);
+        let root = &ir[0];
+        assert_eq!((root.span.line, root.span.column), (2, 1));
+        let list = root.get("ModelOne").unwrap();
+        assert_eq!((list.span.line, list.span.column), (3, 3));
+        let item = &items(list)[0];
+        assert_eq!(keys(item), vec!["PropOne"]);
+        assert_eq!(
+            &content[item.span.start..item.span.end],
+            "PropOne: \"int64\""
+        );
+        assert_eq!(item.get("PropOne").unwrap().span.end, item.span.end);
+    }
+
+    #[test]
+    fn parse_block_scalar() {
+        let content = indoc! {"
+ModelOne:
+  - PropOne:
+      - doc_comment: >-
+          Documentation of
+          the property
+      - type: \"int64\"
+"};
+        let root = parse_root(content);
+        let models = items(root.get("ModelOne").unwrap());
+        let props = items(models[0].get("PropOne").unwrap());
+        assert_eq!(props.len(), 2);
+        assert_eq!(
+            props[0].get("doc_comment").and_then(|doc| doc.as_str()),
+            Some("Documentation of the property")
+        );
+    }
+
+    #[test]
+    fn parse_flow_sequence() {
+        let content = indoc! {"
+numbers: [1, 2, 3]
+players:
+  - [name, \"hr\", [avg, 0.278]]
+  - [ ]
+"};
+        let root = parse_root(content);
+        assert_eq!(keys(&root), vec!["numbers", "players"]);
+        assert_eq!(items(root.get("numbers").unwrap()).len(), 3);
+
+        let players = items(root.get("players").unwrap());
+        assert_eq!(players.len(), 2);
+        let player = items(&players[0]);
+        assert!(matches!(&player[0].object, YamlObject::Str(name) if name == "name"));
+        assert!(matches!(&player[1].object, YamlObject::Str(hr) if hr == "hr"));
+        assert!(matches!(&player[2].object, YamlObject::List(avg) if avg.len() == 2));
+        assert!(items(&players[1]).is_empty());
+    }
+
+    #[test]
+    fn parse_unterminated_flow_sequence() {
+        let mut scanner = Scanner::new();
+        let mut parser = Parser::new();
+        let tokens = scanner.scan("numbers: [1, 2\n").unwrap();
+        let err = parser.parse(&tokens).unwrap_err();
+        assert_eq!(err.kind, YamlParserErrorKind::UnexpectedEof);
+        assert_eq!(err.expected, Some("`,` or `]`".to_string()));
+    }
+
+    #[test]
+    fn parse_flow_mapping() {
+        let content = indoc! {"
+mark: {hr: 65, avg: 0.278}
+nested: {stats: {hr: [1, 2]}, empty}
+"};
+        let root = parse_root(content);
+        assert!(matches!(&root.object, YamlObject::Mapping(entries, false) if entries.len() == 2));
+        let mark = root.get("mark").unwrap();
+        assert!(matches!(&mark.object, YamlObject::Mapping(_, true)));
+        assert_eq!(keys(mark), vec!["hr", "avg"]);
+        assert!(matches!(
+            mark.get("hr").unwrap().object,
+            YamlObject::Int(65)
+        ));
+        assert!(matches!(
+            mark.get("avg").unwrap().object,
+            YamlObject::Float(_)
+        ));
+
+        let nested = root.get("nested").unwrap();
+        assert_eq!(keys(nested), vec!["stats", "empty"]);
+        let stats = nested.get("stats").unwrap();
+        assert!(matches!(&stats.object, YamlObject::Mapping(entries, true) if entries.len() == 1));
+        assert!(matches!(
+            nested.get("empty").unwrap().object,
+            YamlObject::Null
+        ));
+    }
+
+    #[test]
+    fn parse_unterminated_flow_mapping() {
+        let mut scanner = Scanner::new();
+        let mut parser = Parser::new();
+        let tokens = scanner.scan("mark: {hr: 65 avg: 1}\n").unwrap();
+        let err = parser.parse(&tokens).unwrap_err();
+        assert_eq!(err.kind, YamlParserErrorKind::UnexpectedToken);
+        assert_eq!(err.expected, Some("`,` or `}`".to_string()));
+        assert_eq!((err.span.line, err.span.column), (1, 18));
+    }
+
+    #[test]
+    fn parse_plain_scalars() {
+        let content = indoc! {"
+american:
+  - Boston Red Sox
+  - ~
+  - -12
+friends: map[string]int
+"};
+        let root = parse_root(content);
+        let teams = items(root.get("american").unwrap());
+        assert_eq!(teams[0].as_str(), Some("Boston Red Sox"));
+        assert!(matches!(&teams[1].object, YamlObject::Null));
+        assert!(matches!(&teams[2].object, YamlObject::Int(-12)));
+        assert_eq!(
+            root.get("friends").and_then(|friends| friends.as_str()),
+            Some("map[string]int")
+        );
+    }
+
+    #[test]
+    fn parse_multi_document_stream() {
+        let mut scanner = Scanner::new();
+        let mut parser = Parser::new();
+        let content = indoc! {"
+# first document
+%YAML 1.2
+%TAG !model! tag:monkey.dev,2023:
+---
+ModelOne:
+  - PropOne: {type: int64}
+...
+---
+ModelTwo: {}
+--- plain scalar
+...
+"};
+        let tokens = scanner.scan(content).unwrap();
+        let documents = parser.parse(&tokens).unwrap();
+        assert_eq!(documents.len(), 3);
+        let nodes: Vec<&Vec<YamlNode>> = documents
+            .iter()
+            .map(|document| match &document.object {
+                YamlObject::Document(nodes) => nodes,
+                object => panic!("expected a document, found {:?}", object),
+            })
+            .collect();
+        assert!(matches!(&nodes[0][0].object, YamlObject::Comment(_)));
+        assert_eq!(keys(&nodes[0][1]), vec!["ModelOne"]);
+        assert_eq!(keys(&nodes[1][0]), vec!["ModelTwo"]);
+        assert!(matches!(&nodes[2][0].object, YamlObject::Str(value) if value == "plain scalar"));
+        assert_eq!((documents[1].span.line, documents[1].span.column), (8, 1));
+    }
+
+    #[test]
+    fn parse_invalid_directives() {
+        let parse = |content: &str| {
+            let tokens = Scanner::new().scan(content).unwrap();
+            Parser::new().parse(&tokens).unwrap_err()
+        };
+        let err = parse("%YAML 2.0\n---\nkey: 1\n");
+        assert_eq!(err.kind, YamlParserErrorKind::InvalidDirective);
+        assert_eq!(err.found, YamlToken::Directive("YAML 2.0".to_string()));
+
+        let err = parse("%TAG !a! tag:a\n%TAG !a! tag:b\n---\nkey: 1\n");
+        assert_eq!(err.kind, YamlParserErrorKind::InvalidDirective);
+        assert_eq!((err.span.line, err.span.column), (2, 1));
+
+        let err = parse("%YAML 1.2\nkey: 1\n");
+        assert_eq!(err.kind, YamlParserErrorKind::UnexpectedToken);
+        assert_eq!(err.expected, Some("`---`".to_string()));
+    }
+
+    #[test]
+    fn parse_anchors_and_aliases() {
+        let root = parse_root(indoc! {"
+base: &base
+  - type: int64
+  - nullable: true
+other: *base
+tags: [&tag one, *tag]
+"});
+        let base = root.get("base").unwrap();
+        let other = root.get("other").unwrap();
+        // the alias share the node of the anchor
+        match (&base.object, &other.object) {
+            (YamlObject::Reference(anchor, node), YamlObject::Reference(alias, alias_node)) => {
+                assert_eq!(anchor, "base");
+                assert_eq!(alias, "base");
+                assert!(Rc::ptr_eq(node, alias_node));
+                assert!(matches!(&node.object, YamlObject::List(items) if items.len() == 2));
+            }
+            objects => panic!("expected two references, found {:?}", objects),
+        }
+        assert_eq!((other.span.line, other.span.column), (4, 8));
+        let tags: Vec<Option<&str>> = items(root.get("tags").unwrap())
+            .iter()
+            .map(|tag| tag.as_str())
+            .collect();
+        assert_eq!(tags, vec![Some("one"), Some("one")]);
+    }
+
+    #[test]
+    fn parse_anchors_of_keys() {
+        let root = parse_root(indoc! {"
+&name name: Mark
+players:
+  - &player player: Sammy
+  - !!str 65: *player
+stats: {&hr hr: 63}
+keys: [*name, *hr]
+"});
+        // the anchor in front of a key is the one of the key
+        assert!(matches!(
+            &root.object,
+            YamlObject::Mapping(entries, false)
+                if matches!(&entries[0].key.object, YamlObject::Reference(anchor, _) if anchor == "name")
+        ));
+        assert_eq!(keys(&root), vec!["name", "players", "stats", "keys"]);
+        let players = items(root.get("players").unwrap());
+        assert!(matches!(players[0].object, YamlObject::Mapping(..)));
+        assert_eq!(
+            players[1].get("65").and_then(|player| player.as_str()),
+            Some("player")
+        );
+        let keys: Vec<Option<&str>> = items(root.get("keys").unwrap())
+            .iter()
+            .map(|key| key.as_str())
+            .collect();
+        assert_eq!(keys, vec![Some("name"), Some("hr")]);
+
+        // the anchor alone on the line is the one of the mapping
+        let root = parse_root("--- &map\n&key key: value\nalias: *key\n");
+        assert!(matches!(&root.object, YamlObject::Reference(anchor, _) if anchor == "map"));
+        assert_eq!(
+            root.get("alias").and_then(|alias| alias.as_str()),
+            Some("key")
+        );
+    }
+
+    #[test]
+    fn parse_merge_keys() {
+        let root = parse_root(indoc! {"
+Audit: &audit
+  - created_at: {type: int64}
+  - updated_at: {type: int64}
+ModelOne:
+  - <<: *audit
+  - updated_at: {type: string}
+  - name: {<<: *audit, updated_at: string}
+"});
+        let props = items(root.get("ModelOne").unwrap());
+        // the merge key is resolved only inside the item that contains it
+        assert_eq!(props.len(), 3);
+        assert_eq!(keys(&props[0]), vec!["created_at", "updated_at"]);
+        assert_eq!(props[1].get("updated_at").unwrap().span.line, 6);
+        // the keys defined in the mapping are not overridden
+        let name = props[2].get("name").unwrap();
+        assert_eq!(keys(name), vec!["created_at", "updated_at"]);
+        assert_eq!(
+            name.get("updated_at").and_then(|value| value.as_str()),
+            Some("string")
+        );
+    }
+
+    #[test]
+    fn parse_quoted_merge_keys() {
+        let root = parse_root(indoc! {"
+audit: &audit {created_at: int64}
+quoted: {\"<<\": *audit, name: string}
+single: {'<<': *audit}
+tagged: {!!merge \"<<\": *audit, name: string}
+plain: {<<: *audit, \"<<\": string}
+"});
+        // the quoted `<<` is an ordinary key
+        let quoted = root.get("quoted").unwrap();
+        assert_eq!(keys(quoted), vec!["<<", "name"]);
+        assert!(quoted.get("<<").unwrap().resolve().entries().is_some());
+        assert_eq!(keys(root.get("single").unwrap()), vec!["<<"]);
+        let tagged = root.get("tagged").unwrap();
+        assert_eq!(keys(tagged), vec!["created_at", "name"]);
+        let plain = root.get("plain").unwrap();
+        assert_eq!(keys(plain), vec!["created_at", "<<"]);
+    }
+
+    #[test]
+    fn parse_mapping_keys() {
+        let root = parse_root(indoc! {"
+1: one
+2.5: two
+true: three
+~: four
+\"1\": five
+"});
+        let keys: Vec<&YamlObject> = root
+            .entries()
+            .unwrap()
+            .iter()
+            .map(|entry| &entry.key.object)
+            .collect();
+        assert!(matches!(
+            keys[..],
+            [
+                YamlObject::Int(1),
+                YamlObject::Float(_),
+                YamlObject::Bool(true),
+                YamlObject::Null,
+                YamlObject::Str(_)
+            ]
+        ));
+        assert_eq!(root.get("1").and_then(|value| value.as_str()), Some("five"));
+    }
+
+    #[test]
+    fn parse_duplicate_keys() {
+        let parse = |content: &str| {
+            let tokens = Scanner::new().scan(content).unwrap();
+            Parser::new().parse(&tokens).unwrap_err()
+        };
+        let err = parse("a: 1\nb: 2\na: 3\n");
+        assert_eq!(err.kind, YamlParserErrorKind::DuplicateKey);
+        assert_eq!(err.to_string(), "duplicate key `a` at line 3, column 1");
+
+        let err = parse("a: {1: one, 1: two}\n");
+        assert_eq!(err.kind, YamlParserErrorKind::DuplicateKey);
+        assert_eq!((err.span.line, err.span.column), (1, 13));
+    }
+
+    #[test]
+    fn parse_invalid_aliases() {
+        let parse = |content: &str| {
+            let tokens = Scanner::new().scan(content).unwrap();
+            Parser::new().parse(&tokens).unwrap_err()
+        };
+        let err = parse("key: *missing\n");
+        assert_eq!(err.kind, YamlParserErrorKind::UndefinedAlias);
+        assert_eq!(
+            err.to_string(),
+            "undefined alias `*missing` at line 1, column 6"
+        );
+
+        let err = parse("key: &loop [one, *loop]\n");
+        assert_eq!(err.kind, YamlParserErrorKind::RecursiveAlias);
+        assert_eq!((err.span.line, err.span.column), (1, 18));
+
+        // the anchors are defined only inside the document
+        let err = parse("---\nkey: &value 1\n---\nother: *value\n");
+        assert_eq!(err.kind, YamlParserErrorKind::UndefinedAlias);
+
+        let err = parse("value: &value 1\nkey: {<<: *value}\n");
+        assert_eq!(err.kind, YamlParserErrorKind::InvalidMergeKey);
+        assert_eq!((err.span.line, err.span.column), (2, 7));
+    }
+
+    #[test]
+    fn parse_tags() {
+        let root = parse_root(indoc! {"
+%TAG !m! tag:monkey.dev,2023:
+---
+values: [!!str 12, !!int \"42\", !!float 1, !!bool true, !!null null, ! 12]
+hints: [!ref ModelOne, !m!model 12, !<tag:x.com,2023:y> &value z]
+"});
+        let values = items(root.get("values").unwrap());
+        assert!(matches!(&values[0].object, YamlObject::Str(val) if val == "12"));
+        assert!(matches!(values[1].object, YamlObject::Int(42)));
+        assert!(matches!(values[2].object, YamlObject::Float(val) if val == 1.0));
+        assert!(matches!(values[3].object, YamlObject::Bool(true)));
+        assert!(matches!(values[4].object, YamlObject::Null));
+        assert!(matches!(&values[5].object, YamlObject::Str(val) if val == "12"));
+
+        let hints = items(root.get("hints").unwrap());
+        let tags: Vec<Option<&str>> = hints.iter().map(|hint| hint.tag()).collect();
+        assert_eq!(
+            tags,
+            vec![
+                Some("!ref"),
+                Some("tag:monkey.dev,2023:model"),
+                Some("tag:x.com,2023:y")
+            ]
+        );
+        // the application tags do not resolve the plain scalars
+        assert!(matches!(&hints[1].resolve().object, YamlObject::Str(val) if val == "12"));
+        assert!(matches!(hints[2].object, YamlObject::Reference(..)));
+        assert_eq!(
+            (
+                hints[0].span.column,
+                hints[0].span.end - hints[0].span.start
+            ),
+            (9, 13)
+        );
+    }
+
+    #[test]
+    fn parse_invalid_tags() {
+        let parse = |content: &str| {
+            let tokens = Scanner::new().scan(content).unwrap();
+            Parser::new().parse(&tokens).unwrap_err()
+        };
+        let err = parse("key: !!int twelve\n");
+        assert_eq!(err.kind, YamlParserErrorKind::InvalidTag);
+        assert_eq!(
+            err.to_string(),
+            "invalid tag, expected a value of `!!int` at line 1, column 6"
+        );
+
+        let err = parse("key: !m!model value\n");
+        assert_eq!(err.kind, YamlParserErrorKind::InvalidTag);
+        assert_eq!(err.found, YamlToken::Tag("!m!model".to_string()));
+    }
+
+    #[test]
+    fn parse_nested_blocks() {
+        let root = parse_root(indoc! {"
+server:
+    name: main
+    # the ports of the server
+    ports:
+      - 80
+      - 443
+    routes:
+    - path: /
+      methods:
+         - get:
+             auth: false
+         -   - nested
+             - sequence
+    -
+      path: /health
+enabled: true
+"});
+        assert_eq!(keys(&root), vec!["server", "enabled"]);
+        let server = root.get("server").unwrap();
+        assert_eq!(keys(server), vec!["name", "ports", "routes"]);
+        let routes = items(server.get("routes").unwrap());
+        assert_eq!(routes.len(), 2);
+        assert_eq!(keys(&routes[0]), vec!["path", "methods"]);
+        let methods = items(routes[0].get("methods").unwrap());
+        assert_eq!(keys(&methods[0]), vec!["get"]);
+        assert_eq!(keys(methods[0].get("get").unwrap()), vec!["auth"]);
+        assert_eq!(items(&methods[1]).len(), 2);
+        assert_eq!(keys(&routes[1]), vec!["path"]);
+    }
+
+    #[test]
+    fn parse_bad_indentation() {
+        let parse = |content: &str| {
+            let tokens = Scanner::new().scan(content).unwrap();
+            Parser::new().parse(&tokens).unwrap_err()
+        };
+        let err = parse("a:\n    b: 1\n  c: 2\n");
+        assert_eq!(err.kind, YamlParserErrorKind::BadIndentation);
+        assert_eq!(
+            err.to_string(),
+            "bad indentation, expected 0 spaces at line 3, column 3"
+        );
+
+        // a more indented `- 2` is the continuation of the plain scalar
+        let err = parse("a:\n  - 1\n - 2\n");
+        assert_eq!(err.kind, YamlParserErrorKind::BadIndentation);
+        assert_eq!((err.span.line, err.span.column), (3, 2));
+
+        let err = parse("a:\n  b: 1\n c: 2\n");
+        assert_eq!(err.kind, YamlParserErrorKind::BadIndentation);
+        assert_eq!(err.expected, Some("0 spaces".to_string()));
+    }
+
+    #[test]
+    fn parse_comments() {
+        let content = indoc! {"
+# Users of the system
+ModelOne: # the user
+  # Name of the user
+  name: string
+  roles:
+    # default role
+    - admin # all the permissions
+    - guest
+# end of the models
+"};
+        let ir = parse_single_document(content);
+        let root = &ir[0];
+        let entry = &root.entries().unwrap()[0];
+        assert_eq!(entry.comments.leading, vec![" Users of the system"]);
+        assert_eq!(entry.comments.trailing, Some(" the user".to_string()));
+        assert_eq!(
+            entry.comments.doc(),
+            Some("Users of the system".to_string())
+        );
+
+        let props = entry.value.entries().unwrap();
+        assert_eq!(
+            props[0].comments.doc(),
+            Some("Name of the user".to_string())
+        );
+        assert!(props[1].comments.is_empty());
+        let roles = items(&props[1].value);
+        assert_eq!(roles[0].comments.leading, vec![" default role"]);
+        assert_eq!(
+            roles[0].comments.trailing,
+            Some(" all the permissions".to_string())
+        );
+        assert!(roles[1].comments.is_empty());
+        assert!(
+            matches!(&ir[1].object, YamlObject::Comment(content) if content == " end of the models")
+        );
+    }
+
+    #[test]
+    fn parse_long_comment_run() {
+        let run = "  # c\n".repeat(16_000);
+        let ir = parse_single_document(&format!("a:\n{run}"));
+        let value = &ir[0].entries().unwrap()[0].value;
+        assert!(matches!(value.object, YamlObject::Null));
+        assert_eq!(ir.len(), 16_001);
+
+        let root = parse_root(&format!("- a:\n{run}- b\n"));
+        let items = items(&root);
+        assert_eq!(items.len(), 2);
+        assert!(matches!(
+            items[0].entries().unwrap()[0].value.object,
+            YamlObject::Null
+        ));
+
+        let root = parse_root(&format!("a:\n{run}  b: 1\n"));
+        let entry = &root.entries().unwrap()[0].value.entries().unwrap()[0];
+        assert_eq!(entry.comments.leading.len(), 16_000);
+    }
+
+    #[test]
+    fn parse_flow_and_block_same_tree() {
+        assert_same_tree(
+            "PropOne: {doc_comment: \"Documentation document\", type: int64, nullable: true}\n",
+            indoc! {"
+PropOne:
+  doc_comment: Documentation document
+  type: \"int64\"
+  nullable: true
+"},
+        );
+        assert_same_tree("tags: [a, \"b\", 12]\n", "tags:\n  - a\n  - b\n  - 12\n");
+    }
+
+    #[test]
+    fn parse_documents_same_tree() {
+        assert_same_tree(
+            "---\nModelOne: {a: int64}\n...\n---\nModelTwo: {b: int64}\n",
+            "ModelOne: {a: int64}\n---\nModelTwo: {b: int64}\n",
+        );
+    }
+
+    #[test]
+    fn parse_merge_same_tree() {
+        assert_same_tree(
+            indoc! {"
+audit: &audit {created_at: int64, updated_at: int64}
+ModelOne:
+  <<: *audit
+  updated_at: string
+  name: string
+"},
+            indoc! {"
+audit: {created_at: int64, updated_at: int64}
+ModelOne:
+  created_at: int64
+  updated_at: string
+  name: string
+"},
+        );
+    }
+
+    #[test]
+    fn parse_core_tags_same_tree() {
+        assert_same_tree(
+            "count: !!str 12\nhr: !!int \"65\"\nstate: !enum [A]\n",
+            "count: \"12\"\nhr: 65\nstate: !enum [A]\n",
+        );
     }
 }
@@ -3,22 +3,151 @@
 //! definition that we can find in a YAML definition
 //!
 //! author: https://github.com/vincenzopalazzo
+use std::rc::Rc;
+
+use crate::span::Span;
+
+/// Yaml node with the position of the content
+/// where it is defined.
+#[derive(Debug, Clone)]
+pub struct YamlNode {
+    pub object: YamlObject,
+    pub span: Span,
+    /// comments of the node when it is a sequence item
+    pub comments: Comments,
+}
+
+impl YamlNode {
+    pub fn new(object: YamlObject, span: Span) -> Self {
+        YamlNode {
+            object,
+            span,
+            comments: Comments::default(),
+        }
+    }
+
+    /// Follow the references and the tags of the node,
+    /// and return the node that contains the value.
+    pub fn resolve(&self) -> &YamlNode {
+        match &self.object {
+            YamlObject::Reference(_, node) => node.resolve(),
+            YamlObject::Tagged(_, node) => node.resolve(),
+            _ => self,
+        }
+    }
+
+    /// Application tag of the node, e.g: `!ref`, following
+    /// the references.
+    pub fn tag(&self) -> Option<&str> {
+        match &self.object {
+            YamlObject::Reference(_, node) => node.tag(),
+            YamlObject::Tagged(tag, _) => Some(tag),
+            _ => None,
+        }
+    }
+
+    /// Entries of the node if it is a mapping, following
+    /// the references and the tags.
+    pub fn entries(&self) -> Option<&[MappingEntry]> {
+        match &self.resolve().object {
+            YamlObject::Mapping(entries, _) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Value of the mapping entry with the string key
+    /// given in input.
+    pub fn get(&self, key: &str) -> Option<&YamlNode> {
+        self.entries()?
+            .iter()
+            .find(|entry| entry.key_str() == Some(key))
+            .map(|entry| &entry.value)
+    }
+
+    /// Content of the node if it is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.resolve().object {
+            YamlObject::Str(content) => Some(content),
+            _ => None,
+        }
+    }
+}
+
+/// Entry of a mapping with the key and the value node,
+/// the key can be any scalar, e.g: `12: twelve`.
+#[derive(Debug, Clone)]
+pub struct MappingEntry {
+    pub key: YamlNode,
+    pub value: YamlNode,
+    /// comments that annotate the entry
+    pub comments: Comments,
+}
+
+impl MappingEntry {
+    pub fn new(key: YamlNode, value: YamlNode) -> Self {
+        MappingEntry {
+            key,
+            value,
+            comments: Comments::default(),
+        }
+    }
+
+    /// Key of the entry if it is a string.
+    pub fn key_str(&self) -> Option<&str> {
+        self.key.as_str()
+    }
+
+    /// Span of the whole entry, from the key to the value.
+    pub fn span(&self) -> Span {
+        self.key.span.merge(&self.value.span)
+    }
+}
+
+/// Comments that annotate a mapping entry or a sequence item,
+/// the content does not include the `#`, e.g:
+///
+/// # Users of the system
+/// ModelOne: # leading and trailing comment
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Comments {
+    /// comment lines before the entry
+    pub leading: Vec<String>,
+    /// comment on the same line of the entry
+    pub trailing: Option<String>,
+}
+
+impl Comments {
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.trailing.is_none()
+    }
+
+    /// Leading comment lines without the spaces around them,
+    /// joined with a new line.
+    pub fn doc(&self) -> Option<String> {
+        if self.leading.is_empty() {
+            return None;
+        }
+        let lines: Vec<&str> = self.leading.iter().map(|line| line.trim()).collect();
+        Some(lines.join("\n"))
+    }
+}
 
 /// Enum for YAML document definition
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum YamlObject {
     /// Document Object that contains the all the yaml definition
-    Document(Vec<YamlObject>),
-    /// Reference to another Yaml document
-    // TODO: see how the reference works
-    Reference(Box<YamlObject>),
-    /// Mapping value, that can be between { and }.
-    /// if bool is specified, the {} are specified.
-    Mapping(String, Box<YamlObject>, bool),
-    /// The key - value object that can represent a single yaml value
-    KeyVal(String, Box<YamlObject>),
+    Document(Vec<YamlNode>),
+    /// Node with an anchor, e.g: `&audit`, that is shared with
+    /// all the aliases to it, e.g: `*audit`.
+    Reference(String, Rc<YamlNode>),
+    /// Node with an application tag, e.g: `!ref ModelOne`,
+    /// the tag is expanded with the `%TAG` directives.
+    Tagged(String, Box<YamlNode>),
+    /// Mapping with the entries in the order of definition, that
+    /// can be between { and }. if bool is specified, the {} are specified.
+    Mapping(Vec<MappingEntry>, bool),
     /// List Object that contains all list yaml element definition
-    List(Vec<YamlObject>),
+    List(Vec<YamlNode>),
     Str(String),
     Int(i64),
     Float(f64),
@@ -27,20 +156,4 @@ pub enum YamlObject {
     Null,
     /// Yaml Comment that store the content of the comment.
     Comment(String),
-    Fake,
-}
-
-/// Enum definition to mark the end of new scope
-/// or the start of one scope.
-pub enum YamlScope {
-    /// Open a new scope, contains only the position
-    /// where this scope is started
-    OpenScope(u32),
-    /// Close a new scope
-    /// contains the start of the scope and the
-    /// end of new one
-    CloseScope(u32, u32),
-    /// Invalid scope, it contains also an error
-    /// message
-    InvalidScope(String),
 }
@@ -1,8 +1,12 @@
 /// Main Yaml scanner crate
 ///
 /// author: https://github.com/vincenzopalazzo
+use std::error::Error;
+use std::fmt;
+use std::fmt::Formatter;
 use std::vec::Vec;
 
+#[allow(clippy::module_inception)]
 pub mod scanner;
 pub mod tokens;
 
@@ -12,5 +16,72 @@ pub trait YamlScanner<T> {
     fn new() -> Self;
 
     /// Run the scan of the yaml content provided as str
-    fn scan(&mut self, content: &str) -> &Vec<T>;
+    fn scan(&mut self, content: &str) -> Result<Vec<T>, ScannerError>;
+}
+
+/// Kind of error that the scanner can find in the content
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScannerErrorKind {
+    /// Block scalar header with an invalid indicator, e.g: `|x`
+    MalformedBlockScalar,
+    /// Quoted string without the closing quote
+    UnterminatedString,
+    /// Escape sequence not supported by YAML, e.g: `\q`
+    InvalidEscape(char),
+    /// Character that is not part of a valid token
+    UnexpectedChar(char),
+    /// Tab used to indent a line, where only the spaces are allowed
+    TabIndentation,
+    /// Yaml feature that is not supported yet by the scanner
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for ScannerErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ScannerErrorKind::MalformedBlockScalar => {
+                write!(f, "malformed block scalar header, expected `|` or `>` with optional `-`, `+` or indentation")
+            }
+            ScannerErrorKind::UnterminatedString => {
+                write!(f, "unterminated string, expected the closing quote")
+            }
+            ScannerErrorKind::InvalidEscape(escape) => {
+                write!(f, "invalid escape sequence `\\{}`", escape.escape_debug())
+            }
+            ScannerErrorKind::UnexpectedChar(char_at) => {
+                write!(f, "unexpected character `{}`", char_at.escape_debug())
+            }
+            ScannerErrorKind::TabIndentation => {
+                write!(f, "tab in the indentation, expected spaces")
+            }
+            ScannerErrorKind::Unsupported(feature) => write!(f, "{} is not supported yet", feature),
+        }
+    }
+}
+
+/// Scanner error with the position of the content
+/// where the error is found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScannerError {
+    pub kind: ScannerErrorKind,
+    /// line of the error, starting from 1
+    pub line: usize,
+    /// column of the error, starting from 1
+    pub column: usize,
+    /// byte offset of the error inside the content
+    pub offset: usize,
+    /// the content that cause the error
+    pub snippet: String,
+}
+
+impl Error for ScannerError {}
+
+impl fmt::Display for ScannerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.kind, self.line, self.column
+        )
+    }
 }
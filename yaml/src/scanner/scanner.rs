@@ -1,252 +1,1006 @@
 /// Core implementation of the YAML scanner
 ///
 /// author: https://github.com/vincenzopalazzo
+use std::collections::VecDeque;
+
 use super::tokens::*;
-use super::YamlScanner;
+use super::{ScannerError, ScannerErrorKind, YamlScanner};
+use crate::span::Span;
+
+/// UTF-8 byte order mark, that is not part of the content.
+const BOM: char = '\u{feff}';
 
-/// Core implementation of the scanner
+/// Core implementation of the scanner, that read the content
+/// once from the byte offset of the current position.
 pub struct Scanner {
+    /// chars consumed from the start of the content
     pos: usize,
-    line: u64,
-    tokens: Vec<YamlToken>,
-    identifier_symbols: Vec<char>,
+    /// byte offset of the current position
+    offset: usize,
+    line: usize,
+    /// position where the current line start
+    line_start: usize,
+    /// byte offset where the current line start
+    line_offset: usize,
+    /// span where the current token start
+    token_start: Span,
+    tokens: VecDeque<Token>,
+    /// number of flow collections opened, e.g: `[` or `{`
+    flow_level: usize,
+    /// increasing indentations of the lines before the current one,
+    /// where the last is the indentation of the last line with content
+    /// and each other is the one of the first line before it that is
+    /// less indented, e.g: `[0, 2]` for `a:\n  b:\n    c: 1\n  d:`
+    line_indents: Vec<usize>,
+    /// byte offset of the first line that is not in `line_indents` yet
+    line_indents_offset: usize,
 }
 
 impl Scanner {
     /// Entry point of the scanner to parser
     /// the string and return a list of token
-    fn run(&mut self, content: &str) {
-        while !self.is_at_end(&content) {
-            self.contrue_token(&content);
+    fn run(&mut self, content: &str) -> Result<(), ScannerError> {
+        while !self.is_at_end(content) {
+            self.contrue_token(content)?;
         }
+        self.token_start = self.current_span();
         self.add_token(YamlToken::EOF);
+        Ok(())
     }
 
     /// Core function to try to understand the
     /// core function of the scanner too contrue
     /// the token from the content
-    fn contrue_token(&mut self, content: &str) {
-        let char_token = self.next(&content);
+    fn contrue_token(&mut self, content: &str) -> Result<(), ScannerError> {
+        self.token_start = self.current_span();
+        let char_token = self.next(content);
         match char_token {
-            '\n' => self.line += 1,
+            '\n' => self.new_line(),
             ' ' => self.add_token(YamlToken::Space),
+            // the tabs can divide the tokens, but they can not
+            // be used for the indentation
+            '\t' if self.is_indentation() => {
+                return Err(self.error(content, ScannerErrorKind::TabIndentation));
+            }
+            '\t' => self.add_token(YamlToken::TabSpace),
             ':' => self.add_token(YamlToken::DotDot),
-            '|' | '>' => {
-                let multiline = "TODO: need to parse multiline content";
-                self.add_token(YamlToken::Multiline(multiline.to_string()));
-                todo!("{}", multiline);
-            }
-            '[' => self.add_token(YamlToken::LeftSquareBrace),
-            ']' => self.add_token(YamlToken::RightCurlyBrace),
-            '{' => self.add_token(YamlToken::LeftCurlyBrace),
-            '}' => self.add_token(YamlToken::RightCurlyBrace),
-            '\'' | '"' => self.parse_string(content),
-            '-' => {
-                // Check if the first dash is the item list
-                // or it is a token to start a new document
-                if self.advance_if_match(content, '-') {
-                    // we need another - otherwise we emit an error
-                    if self.advance_if_match(content, '-') {
-                        self.add_token(YamlToken::StartDoc);
-                    } else {
-                        // TODO emit a scanner error here
-                        panic!("Error we have a from start document token");
-                    }
-                } else {
-                    self.add_token(YamlToken::Dash);
-                }
+            ',' => self.add_token(YamlToken::Comma),
+            '|' | '>' => self.parse_block_scalar(char_token == '>', content)?,
+            '[' => {
+                self.flow_level += 1;
+                self.add_token(YamlToken::LeftSquareBrace)
             }
-            '.' => {
-                if self.advance_if_match(content, '.') && self.advance_if_match(content, '.') {
-                    self.add_token(YamlToken::EndDoc);
-                } else {
-                    // TODO emit scanner errors
-                    panic!("End document token malformed");
+            ']' => {
+                self.flow_level = self.flow_level.saturating_sub(1);
+                self.add_token(YamlToken::RightSquareBrace)
+            }
+            '{' => {
+                self.flow_level += 1;
+                self.add_token(YamlToken::LeftCurlyBrace)
+            }
+            '}' => {
+                self.flow_level = self.flow_level.saturating_sub(1);
+                self.add_token(YamlToken::RightCurlyBrace)
+            }
+            '\'' | '"' => self.parse_string(char_token, content)?,
+            '-' | '.' if self.is_document_marker(content, char_token) => {
+                self.next(content);
+                self.next(content);
+                match char_token {
+                    '-' => self.add_token(YamlToken::StartDoc),
+                    _ => self.add_token(YamlToken::EndDoc),
                 }
             }
-            '#' => self.parse_comment_line(&content),
-            _ => {
-                if !self.parse_number(char_token, content)
-                    && !self.parse_identifier(char_token, content)
-                {
-                    panic!(
-                        "Content not recognized at line {}: {}",
-                        self.line, char_token
-                    );
+            // e.g: `-12`, `-value` or `--verbose`
+            '-' if self.is_plain_safe(content) => self.parse_plain_scalar(char_token, content)?,
+            '-' => self.add_token(YamlToken::Dash),
+            // e.g: `.inf`, `.5` or `../models`
+            '.' => self.parse_plain_scalar(char_token, content)?,
+            '#' => self.parse_comment_line(content),
+            // the directives are defined only at the beginning of the line
+            '%' if self.pos == self.line_start + 1 => self.parse_directive(content),
+            '?' if self.is_plain_safe(content) => self.parse_plain_scalar(char_token, content)?,
+            '&' | '*' if self.is_plain_safe(content) => {
+                let name = self.parse_anchor_name(content);
+                match char_token {
+                    '&' => self.add_token(YamlToken::Anchor(name)),
+                    _ => self.add_token(YamlToken::Alias(name)),
                 }
-                // Try to decode the number
-                // and the string
             }
+            '!' => self.parse_tag(content),
+            '?' | '&' | '*' | '%' | '@' | '`' => {
+                return Err(self.error(content, ScannerErrorKind::UnexpectedChar(char_token)));
+            }
+            _ => self.parse_plain_scalar(char_token, content)?,
         };
+        Ok(())
+    }
+
+    /// Build the scanner error of the token that start
+    /// at the beginning of the current token.
+    fn error(&self, content: &str, kind: ScannerErrorKind) -> ScannerError {
+        self.error_from(content, kind, self.token_start)
+    }
+
+    /// Build the scanner error of the content that start at
+    /// the span given in input.
+    fn error_from(&self, content: &str, kind: ScannerErrorKind, start: Span) -> ScannerError {
+        ScannerError {
+            kind,
+            line: start.line,
+            column: start.column,
+            offset: start.start,
+            snippet: content[start.start..self.offset].to_string(),
+        }
+    }
+
+    /// Empty span at the current position
+    fn current_span(&self) -> Span {
+        Span::new(
+            self.offset,
+            self.offset,
+            self.line,
+            self.pos - self.line_start + 1,
+        )
+    }
+
+    /// Check if the marker char, already consumed, start a document
+    /// marker like `---` or `...`, that is defined only at the
+    /// beginning of the line and followed by a blank.
+    fn is_document_marker(&self, stream: &str, marker: char) -> bool {
+        let chars = std::iter::once(marker).chain(line_breaks(&stream[self.offset..]));
+        self.pos == self.line_start + 1 && is_document_marker(chars)
+    }
+
+    /// Check if the current token is part of the indentation
+    /// of the line, so there are only spaces before it.
+    fn is_indentation(&self) -> bool {
+        self.tokens
+            .iter()
+            .rev()
+            .take_while(|token| token.span.line == self.line)
+            .all(|token| token.kind == YamlToken::Space)
+    }
+
+    /// Mark the start of a new line at the current position
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.line_start = self.pos;
+        self.line_offset = self.offset;
     }
 
     /// Function to parse the comment line and store
     /// the content of the comment inside the function
     fn parse_comment_line(&mut self, content: &str) {
         let mut line_comment = String::from("");
-        let mut start_comment = self.pos;
 
-        while content.chars().nth(start_comment).unwrap() != '\n' {
-            line_comment.push(content.chars().nth(start_comment).unwrap());
-            start_comment += 1;
+        while !self.is_at_end(content) && self.peek(content) != '\n' {
+            line_comment.push(self.next(content));
         }
-        println!("pos {} with val {}", self.pos, line_comment);
         let token = YamlToken::Pount(line_comment);
         self.add_token(token);
-        // with increase the value by one because we want
-        // skip the \n in the comment line
-        self.pos = start_comment + 1;
-        self.line += 1;
     }
 
-    fn is_valid_for_str(&self, char_at: char) -> bool {
-        char_at != '\'' && char_at != '"'
+    /// Parse a directive line like `%YAML 1.2` or `%TAG ! tag:example.com,2000:`
+    /// without the comment at the end of the line.
+    fn parse_directive(&mut self, content: &str) {
+        let mut directive = String::new();
+        while !self.is_at_end(content) && self.peek(content) != '\n' {
+            if directive.ends_with([' ', '\t']) && self.peek(content) == '#' {
+                break;
+            }
+            directive.push(self.next(content));
+        }
+        let directive = directive.trim_end().to_string();
+        self.add_token(YamlToken::Directive(directive));
+    }
+
+    /// Parse the name of an anchor (`&name`) or of an alias (`*name`),
+    /// that end with a blank or with a flow indicator.
+    fn parse_anchor_name(&mut self, stream: &str) -> String {
+        let mut name = String::new();
+        while let Some(char_at) = self.peek_at(stream, 0) {
+            if char_at.is_whitespace() || is_flow_indicator(char_at) {
+                break;
+            }
+            name.push(self.next(stream));
+        }
+        name
+    }
+
+    /// Parse the tag of a node, that can be a shorthand like `!!str`,
+    /// `!ref` and `!name!suffix` or a verbatim tag like `!<tag:x.com>`.
+    fn parse_tag(&mut self, stream: &str) {
+        let mut tag = String::from("!");
+        if self.advance_if_match(stream, '<') {
+            tag.push('<');
+            while let Some(char_at) = self.peek_at(stream, 0) {
+                if char_at.is_whitespace() {
+                    break;
+                }
+                tag.push(self.next(stream));
+                if char_at == '>' {
+                    break;
+                }
+            }
+        } else {
+            tag.push_str(&self.parse_anchor_name(stream));
+        }
+        self.add_token(YamlToken::Tag(tag));
     }
 
-    /// Parsing a string contained inside a ' or ".
-    fn parse_string(&mut self, stream: &str) {
-        if self.is_at_end(stream) {
-            return;
+    /// Check if the node that we are scanning has a tag on the
+    /// same line, in front of it or of its anchor.
+    fn has_tag(&self) -> bool {
+        self.tokens
+            .iter()
+            .rev()
+            .take_while(|token| {
+                token.span.line == self.token_start.line
+                    && matches!(
+                        token.kind,
+                        YamlToken::Space | YamlToken::Anchor(_) | YamlToken::Tag(_)
+                    )
+            })
+            .any(|token| matches!(token.kind, YamlToken::Tag(_)))
+    }
+
+    /// Parse a literal (`|`) or folded (`>`) block scalar, with the
+    /// optional chomping (`-`, `+`) and indentation indicators, e.g:
+    ///
+    /// doc_comment: >-
+    ///   This is a long comment
+    ///   on two lines
+    fn parse_block_scalar(&mut self, folded: bool, stream: &str) -> Result<(), ScannerError> {
+        // the content is more indented than the parent node, where
+        // the root node is at the indentation -1
+        let min_indent = self
+            .parent_indent(stream)
+            .map_or(0, |parent_indent| parent_indent + 1);
+        let mut chomping = Chomping::Clip;
+        let mut indent = None;
+        while let Some(char_at) = self.peek_at(stream, 0) {
+            match char_at {
+                '-' if chomping == Chomping::Clip => chomping = Chomping::Strip,
+                '+' if chomping == Chomping::Clip => chomping = Chomping::Keep,
+                // the indentation indicator is relative to the parent node
+                '1'..='9' if indent.is_none() => {
+                    indent = char_at
+                        .to_digit(10)
+                        .map(|digit| min_indent + digit as usize - 1)
+                }
+                _ => break,
+            }
+            self.next(stream);
         }
-        let start = self.pos;
-        let mut start_char = self.peek(stream);
-        while self.is_valid_for_str(start_char) && !self.is_at_end(stream) {
-            if start_char == '\n' {
-                self.line += 1;
+        // only spaces and a comment can follow the header
+        while self.peek_at(stream, 0) == Some(' ') {
+            self.next(stream);
+        }
+        if self.peek_at(stream, 0) == Some('#') {
+            while !self.is_at_end(stream) && self.peek(stream) != '\n' {
+                self.next(stream);
+            }
+        }
+        match self.peek_at(stream, 0) {
+            Some('\n') | None => {}
+            Some(_) => return Err(self.error(stream, ScannerErrorKind::MalformedBlockScalar)),
+        }
+
+        // the block scalar token end with the last content line, so
+        // we keep the end before eating the trailing empty lines.
+        let mut end = self.offset;
+        let mut lines: Vec<String> = vec![];
+        let mut trailing_breaks = 0;
+        let mut last_break = false;
+        while self.advance_if_match(stream, '\n') {
+            self.new_line();
+            let spaces = self.count_spaces_at(stream);
+            // a root block scalar end at the next document marker
+            if spaces == 0 && is_document_marker(line_breaks(&stream[self.offset..])) {
+                break;
+            }
+            let is_empty = matches!(self.peek_at(stream, spaces), Some('\n') | None);
+            if is_empty {
+                // empty lines are part of the scalar until we find
+                // a line that is less indented.
+                for _ in 0..spaces {
+                    self.next(stream);
+                }
+                if self.peek_at(stream, 0).is_some() {
+                    trailing_breaks += 1;
+                }
+                continue;
+            }
+            // auto detect the indentation from the first content line
+            let block_indent = *indent.get_or_insert(spaces.max(min_indent));
+            if spaces < block_indent {
+                // the line close the block, and it is not consumed
+                // so its indentation is scanned as usual.
                 break;
             }
-            start_char = self.next(stream);
+            for _ in 0..trailing_breaks {
+                lines.push(String::new());
+            }
+            trailing_breaks = 0;
+            for _ in 0..block_indent {
+                self.next(stream);
+            }
+            let mut line = String::new();
+            while !self.is_at_end(stream) && self.peek(stream) != '\n' {
+                line.push(self.next(stream));
+            }
+            lines.push(line);
+            end = self.offset;
+            last_break = !self.is_at_end(stream);
         }
-        let identifier = &stream[start..self.pos];
-        self.add_token(YamlToken::StringVal(identifier.to_string()));
+
+        let mut value = if folded {
+            fold_lines(&lines)
+        } else {
+            lines.join("\n")
+        };
+        match chomping {
+            Chomping::Strip => {}
+            Chomping::Clip if lines.is_empty() || !last_break => {}
+            Chomping::Clip => value.push('\n'),
+            Chomping::Keep => {
+                if !lines.is_empty() && last_break {
+                    value.push('\n');
+                }
+                value.push_str(&"\n".repeat(trailing_breaks));
+            }
+        }
+        let span = Span {
+            end,
+            ..self.token_start
+        };
+        self.tokens
+            .push_back(Token::new(YamlToken::Multiline(value), span));
+        Ok(())
+    }
+
+    /// Count the spaces from the current position without
+    /// consume them.
+    fn count_spaces_at(&self, stream: &str) -> usize {
+        line_breaks(&stream[self.offset..])
+            .take_while(|char_at| *char_at == ' ')
+            .count()
     }
 
-    /// Decode a in the correct Yaml Token a correct number, in the correct
-    /// form
-    fn parse_number<'a>(&'a mut self, char_at: char, stream: &'a str) -> bool {
-        if char_at.is_numeric() {
-            let mut is_decimal = false;
-            let start = self.pos - 1;
-            let mut start_dig = self.next(stream);
-            while start_dig.is_numeric() {
-                start_dig = self.next(stream);
+    /// Parsing a string contained inside a ' or ", where the single
+    /// quoted string escape the quote with `''` and the double quoted
+    /// string support the YAML escape sequences. The line breaks inside
+    /// the string are folded.
+    fn parse_string(&mut self, quote: char, stream: &str) -> Result<(), ScannerError> {
+        let mut value = String::new();
+        loop {
+            let Some(char_at) = self.peek_at(stream, 0) else {
+                return Err(self.error(stream, ScannerErrorKind::UnterminatedString));
+            };
+            match char_at {
+                '\'' if quote == '\'' => {
+                    self.next(stream);
+                    if !self.advance_if_match(stream, '\'') {
+                        break;
+                    }
+                    value.push('\'');
+                }
+                '"' if quote == '"' => {
+                    self.next(stream);
+                    break;
+                }
+                '\\' if quote == '"' => self.parse_escape(stream, &mut value)?,
+                ' ' | '\t' => {
+                    // the trailing spaces of a line are not part of the string
+                    let blanks = self.count_blanks_at(stream, 0);
+                    let trailing = self.peek_at(stream, blanks) == Some('\n');
+                    for _ in 0..blanks {
+                        let blank = self.next(stream);
+                        if !trailing {
+                            value.push(blank);
+                        }
+                    }
+                }
+                '\n' => {
+                    let empty_lines = self.skip_string_line_breaks(stream);
+                    // a single line break is folded in a space
+                    match empty_lines {
+                        0 => value.push(' '),
+                        _ => value.push_str(&"\n".repeat(empty_lines)),
+                    }
+                }
+                _ => value.push(self.next(stream)),
             }
+        }
+        self.add_token(YamlToken::StringVal(value));
+        Ok(())
+    }
 
-            if start_dig == '.' {
-                is_decimal = true;
-                start_dig = self.next(stream);
-                while start_dig.is_numeric() {
-                    start_dig = self.next(stream);
+    /// Consume the line breaks inside a quoted string with the leading
+    /// spaces of the next line, and return the number of empty lines.
+    fn skip_string_line_breaks(&mut self, stream: &str) -> usize {
+        let mut empty_lines = 0;
+        while self.advance_if_match(stream, '\n') {
+            self.new_line();
+            while matches!(self.peek_at(stream, 0), Some(' ') | Some('\t')) {
+                self.next(stream);
+            }
+            if self.peek_at(stream, 0) == Some('\n') {
+                empty_lines += 1;
+            }
+        }
+        empty_lines
+    }
+
+    /// Decode the escape sequence of a double quoted string,
+    /// e.g: `\n`, `\"` or `\u00e8`.
+    fn parse_escape(&mut self, stream: &str, value: &mut String) -> Result<(), ScannerError> {
+        let start = self.current_span();
+        // consume the `\`
+        self.next(stream);
+        let Some(escape) = self.peek_at(stream, 0) else {
+            return Err(self.error(stream, ScannerErrorKind::UnterminatedString));
+        };
+        self.next(stream);
+        let decoded = match escape {
+            '0' => '\0',
+            'a' => '\u{07}',
+            'b' => '\u{08}',
+            't' | '\t' => '\t',
+            'n' => '\n',
+            'v' => '\u{0B}',
+            'f' => '\u{0C}',
+            'r' => '\r',
+            'e' => '\u{1B}',
+            ' ' => ' ',
+            '"' => '"',
+            '/' => '/',
+            '\\' => '\\',
+            'N' => '\u{85}',
+            '_' => '\u{A0}',
+            'L' => '\u{2028}',
+            'P' => '\u{2029}',
+            'x' | 'u' | 'U' => {
+                let len = match escape {
+                    'x' => 2,
+                    'u' => 4,
+                    _ => 8,
+                };
+                let mut code = String::new();
+                for _ in 0..len {
+                    match self.peek_at(stream, 0) {
+                        Some(digit) if digit.is_ascii_hexdigit() => code.push(self.next(stream)),
+                        _ => break,
+                    }
+                }
+                let decoded = u32::from_str_radix(&code, 16)
+                    .ok()
+                    .filter(|_| code.len() == len)
+                    .and_then(char::from_u32);
+                match decoded {
+                    Some(decoded) => decoded,
+                    None => {
+                        let kind = ScannerErrorKind::InvalidEscape(escape);
+                        return Err(self.error_from(stream, kind, start));
+                    }
                 }
             }
-            // restore the token that the while has consumed
-            self.pos -= 1;
-            let number = &stream[start..self.pos];
-            if !is_decimal {
-                self.add_token(YamlToken::IntVal(number.parse().unwrap()));
-            } else {
-                self.add_token(YamlToken::FloatVal(number.parse().unwrap()));
+            '\n' => {
+                // escaped line break, the lines are joined without space
+                self.new_line();
+                while matches!(self.peek_at(stream, 0), Some(' ') | Some('\t')) {
+                    self.next(stream);
+                }
+                return Ok(());
+            }
+            _ => {
+                let kind = ScannerErrorKind::InvalidEscape(escape);
+                return Err(self.error_from(stream, kind, start));
+            }
+        };
+        value.push(decoded);
+        Ok(())
+    }
+
+    /// Parse a plain scalar, that is a scalar without quotes that can
+    /// contain spaces and continue on the next lines when they are more
+    /// indented, e.g: `- Boston Red Sox`, and resolve it with the
+    /// YAML 1.2 core schema. The first char is already consumed.
+    fn parse_plain_scalar(&mut self, first: char, stream: &str) -> Result<(), ScannerError> {
+        // found at the first line break, because the most of the
+        // scalars end on the line where they start
+        let mut parent_indent: Option<Option<usize>> = None;
+        let mut value = String::from(first);
+        loop {
+            match self.peek_at(stream, 0) {
+                Some(' ') | Some('\t') => {
+                    // the trailing spaces are not part of the scalar
+                    let spaces = self.count_blanks_at(stream, 0);
+                    if self.is_plain_end(stream, spaces)
+                        || self.peek_at(stream, spaces) == Some('#')
+                    {
+                        break;
+                    }
+                    for _ in 0..spaces {
+                        value.push(self.next(stream));
+                    }
+                }
+                Some('\n') => {
+                    // the flow scalars continue whatever is their indentation
+                    if parent_indent.is_none() && self.flow_level == 0 {
+                        parent_indent = Some(self.parent_indent(stream));
+                    }
+                    match self.plain_continuation(stream, parent_indent.flatten()) {
+                        Some(empty_lines) => {
+                            for _ in 0..=empty_lines {
+                                self.advance_if_match(stream, '\n');
+                                self.new_line();
+                                while matches!(self.peek_at(stream, 0), Some(' ') | Some('\t')) {
+                                    self.next(stream);
+                                }
+                            }
+                            // a single line break is folded in a space
+                            match empty_lines {
+                                0 => value.push(' '),
+                                _ => value.push_str(&"\n".repeat(empty_lines)),
+                            }
+                        }
+                        None => break,
+                    }
+                }
+                _ if self.is_plain_end(stream, 0) => break,
+                Some(_) => value.push(self.next(stream)),
+                None => break,
             }
-            return true;
         }
-        false
+        // the tag decide the type of the scalar, e.g: `!!str 12`
+        if self.has_tag() {
+            self.add_token(YamlToken::Identifier(value));
+            return Ok(());
+        }
+        self.add_token(resolve_plain_scalar(&value));
+        Ok(())
     }
 
-    /// Check if a char contains a valid token for the an identifier
-    fn valid_identifier_symbol(&self, char_at: char) -> bool {
-        char_at.is_alphanumeric() || self.identifier_symbols.contains(&char_at)
+    /// Check if the plain scalar end at `ahead` chars from the
+    /// current position.
+    fn is_plain_end(&self, stream: &str, ahead: usize) -> bool {
+        match self.peek_at(stream, ahead) {
+            None | Some('\n') => true,
+            // `: ` divide the key from the value
+            Some(':') => match self.peek_at(stream, ahead + 1) {
+                None | Some(' ') | Some('\t') | Some('\n') => true,
+                Some(next) => self.flow_level > 0 && is_flow_indicator(next),
+            },
+            Some(char_at) => self.flow_level > 0 && is_flow_indicator(char_at),
+        }
     }
 
-    /// Decode in a YamlToken an valid identifier
-    fn parse_identifier<'a>(&'a mut self, char_at: char, stream: &'a str) -> bool {
-        if self.is_at_end(stream) {
-            return true;
+    /// Check if the plain scalar continue on the next lines, and return
+    /// the number of empty lines before the continuation line, where
+    /// the continuation line is more indented than the parent node.
+    fn plain_continuation(&self, stream: &str, parent_indent: Option<usize>) -> Option<usize> {
+        let mut ahead = 0;
+        let mut empty_lines = 0;
+        loop {
+            // skip the line break
+            ahead += 1;
+            let spaces = self.count_blanks_at(stream, ahead);
+            match self.peek_at(stream, ahead + spaces) {
+                None | Some('#') => return None,
+                Some('\n') => {
+                    empty_lines += 1;
+                    ahead += spaces;
+                }
+                Some(_) => {
+                    let continuation = self.flow_level > 0
+                        || parent_indent.is_none_or(|parent_indent| spaces > parent_indent);
+                    // a root scalar end at the next document marker
+                    let line = line_breaks(&stream[self.offset..]).skip(ahead);
+                    if !continuation
+                        || (spaces == 0 && is_document_marker(line))
+                        || self.is_plain_end(stream, ahead + spaces)
+                    {
+                        return None;
+                    }
+                    return Some(empty_lines);
+                }
+            }
         }
-        if self.valid_identifier_symbol(char_at) {
-            let start = self.pos - 1;
-            let mut start_char = self.peek(stream);
-            while self.valid_identifier_symbol(start_char) && !self.is_at_end(stream) {
-                start_char = self.next(stream);
-            }
-            // restore the token that the while has consumed
-            self.pos = if self.is_at_end(stream) {
-                self.pos
-            } else {
-                self.pos - 1
-            };
-            let identifier = &stream[start..self.pos];
-            match identifier {
-                "true" => self.add_token(YamlToken::BoolVal(true)),
-                "false" => self.add_token(YamlToken::BoolVal(false)),
-                _ => self.add_token(YamlToken::Identifier(identifier.to_string())),
+    }
+
+    /// Count the spaces and tabs at `ahead` chars from the current
+    /// position without consume them.
+    fn count_blanks_at(&self, stream: &str, ahead: usize) -> usize {
+        line_breaks(&stream[self.offset..])
+            .skip(ahead)
+            .take_while(|char_at| *char_at == ' ' || *char_at == '\t')
+            .count()
+    }
+
+    /// Indentation of the block node that contains the scalar that
+    /// start at the current token, or None if the scalar is a root
+    /// node. The parent is the key on the same line of the scalar,
+    /// e.g: `key: value`, or the last `-` before it, e.g: `- item`,
+    /// otherwise the first line before the scalar that is less
+    /// indented than it, e.g: `key:` for `key:\n  value`.
+    fn parent_indent(&mut self, stream: &str) -> Option<usize> {
+        let mut key = false;
+        let mut dash = None;
+        let mut first = None;
+        let line_tokens = self
+            .tokens
+            .iter()
+            .rev()
+            .take_while(|token| token.span.line == self.token_start.line);
+        for token in line_tokens {
+            match token.kind {
+                YamlToken::DotDot => key = true,
+                YamlToken::Dash => dash = dash.or(Some(token.span.column - 1)),
+                YamlToken::Space | YamlToken::TabSpace => continue,
+                _ => {}
+            }
+            if token.kind != YamlToken::Dash {
+                first = Some(token.span.column - 1);
             }
-            return true;
         }
-        false
+        if key {
+            return first;
+        }
+        if dash.is_some() {
+            return dash;
+        }
+        self.update_line_indents(stream);
+        let indent = self.count_spaces_at_line(stream, self.line_offset);
+        // the lines more indented than the current one can not
+        // be the parent of the following lines
+        while self.line_indents.last().is_some_and(|last| *last >= indent) {
+            self.line_indents.pop();
+        }
+        self.line_indents.last().copied()
+    }
+
+    /// Add the indentation of the lines before the current one to
+    /// `line_indents`, so each line of the content is read once.
+    fn update_line_indents(&mut self, stream: &str) {
+        let lines = &stream[self.line_indents_offset..self.line_offset];
+        self.line_indents_offset = self.line_offset;
+        for line in lines.split(['\n', '\r']) {
+            let content = line.trim_start_matches(' ');
+            if content.is_empty() || content.starts_with('#') {
+                continue;
+            }
+            // the lines after it are part of the root node of a document
+            if content.starts_with('%') || is_document_marker(line.chars().chain(['\n'])) {
+                self.line_indents.clear();
+                continue;
+            }
+            let indent = line.len() - content.len();
+            while self.line_indents.last().is_some_and(|last| *last >= indent) {
+                self.line_indents.pop();
+            }
+            self.line_indents.push(indent);
+        }
+    }
+
+    /// Count the spaces at the start of the line that
+    /// start at the offset given in input.
+    fn count_spaces_at_line(&self, stream: &str, offset: usize) -> usize {
+        stream[offset..]
+            .chars()
+            .take_while(|char_at| *char_at == ' ')
+            .count()
     }
 
     /// Function to add a token inside the list of tokens founds
     // TODO: find the error generated by the token passed by reference
     // the error refers to the copy, but I don't know much
-    fn add_token<'a>(&'a mut self, token: YamlToken) {
-        self.tokens.push(token);
+    fn add_token(&mut self, token: YamlToken) {
+        let span = Span {
+            end: self.offset,
+            ..self.token_start
+        };
+        self.tokens.push_back(Token::new(token, span));
     }
 
     /// Peek the value in the current position
     fn peek(&self, stream: &str) -> char {
-        stream.chars().nth(self.pos).unwrap()
+        self.peek_at(stream, 0).unwrap()
+    }
+
+    /// Check if the char in the current position can follow an
+    /// indicator like `-` at the start of a plain scalar.
+    fn is_plain_safe(&self, stream: &str) -> bool {
+        match self.peek_at(stream, 0) {
+            None | Some(' ') | Some('\t') | Some('\n') => false,
+            Some(char_at) => self.flow_level == 0 || !is_flow_indicator(char_at),
+        }
+    }
+
+    /// Peek the value at `ahead` chars from the current position
+    /// or None if the position is outside the stream.
+    fn peek_at(&self, stream: &str, ahead: usize) -> Option<char> {
+        line_breaks(&stream[self.offset..]).nth(ahead)
     }
 
-    /// Take the next token in the stream
-    // TODO: can be improvend in performance?
+    /// Take the next char in the stream, where the line
+    /// breaks `\r\n` and `\r` are taken as a single `\n`.
     fn next(&mut self, stream: &str) -> char {
+        let char_at = stream[self.offset..].chars().next().unwrap();
         self.pos += 1;
-        stream.chars().nth(self.pos - 1).unwrap()
+        self.offset += char_at.len_utf8();
+        if char_at == '\r' {
+            if stream[self.offset..].starts_with('\n') {
+                self.offset += 1;
+            }
+            return '\n';
+        }
+        char_at
     }
 
     /// Advance in the stream if exist a token that match the
     /// target element given in input.
     /// Return the result of the operation as boolean value
     fn advance_if_match(&mut self, stream: &str, target: char) -> bool {
-        if self.is_at_end(stream) {
-            return false;
-        }
-        let elem = stream.chars().nth(self.pos).unwrap();
-        if elem == target {
-            self.pos += 1;
+        if self.peek_at(stream, 0) == Some(target) {
+            self.next(stream);
             return true;
         }
         false
     }
 
     /// Check if we reach the end of the content
-    /// by checking the lenght of the token with
-    /// the actual position
+    /// by checking the lenght of the content with
+    /// the actual byte offset
     fn is_at_end(&self, content: &str) -> bool {
-        content.len() <= self.pos
+        content.len() <= self.offset
+    }
+
+    /// Reset the position of the scanner to the start of the
+    /// content, after the byte order mark if the content has it.
+    fn reset(&mut self, content: &str) {
+        self.pos = 0;
+        self.offset = 0;
+        if content.starts_with(BOM) {
+            // the first char after the mark is at column 1
+            self.pos = 1;
+            self.offset = BOM.len_utf8();
+        }
+        self.line = 1;
+        self.line_start = self.pos;
+        self.line_offset = self.offset;
+        self.flow_level = 0;
+        self.tokens.clear();
+        self.line_indents.clear();
+        self.line_indents_offset = self.offset;
+    }
+
+    /// Iterate over the tokens of the content, the content is
+    /// scanned while the tokens are consumed, e.g:
+    ///
+    /// let tokens = Scanner::new().tokens("a: 1").collect::<Result<Vec<_>, _>>();
+    pub fn tokens(mut self, content: &str) -> Tokens<'_> {
+        self.reset(content);
+        Tokens {
+            scanner: self,
+            content,
+            returned: 0,
+            done: false,
+        }
+    }
+}
+
+/// Iterator over the tokens of a YAML content, that keeps
+/// only the tokens of the line that we are scanning.
+pub struct Tokens<'a> {
+    scanner: Scanner,
+    content: &'a str,
+    /// tokens at the front of the scanner already returned
+    returned: usize,
+    /// the EOF token or an error is found
+    done: bool,
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Result<Token, ScannerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.returned == self.scanner.tokens.len() {
+            if self.done {
+                return None;
+            }
+            // the scanner looks back only at the tokens of the current
+            // line, so the tokens returned before it can be dropped
+            while self.returned > 0
+                && self.scanner.tokens.front().map(|token| token.span.line)
+                    != Some(self.scanner.line)
+            {
+                self.scanner.tokens.pop_front();
+                self.returned -= 1;
+            }
+            if self.scanner.is_at_end(self.content) {
+                self.scanner.token_start = self.scanner.current_span();
+                self.scanner.add_token(YamlToken::EOF);
+                self.done = true;
+            } else if let Err(err) = self.scanner.contrue_token(self.content) {
+                self.done = true;
+                return Some(Err(err));
+            }
+        }
+        let token = self.scanner.tokens[self.returned].clone();
+        self.returned += 1;
+        Some(Ok(token))
+    }
+}
+
+/// Chomping indicator of a block scalar, that control
+/// what to do with the trailing line breaks.
+#[derive(Debug, PartialEq)]
+enum Chomping {
+    /// `-`, remove all the trailing line breaks
+    Strip,
+    /// default, keep only the last line break
+    Clip,
+    /// `+`, keep all the trailing line breaks
+    Keep,
+}
+
+/// Fold the lines of a folded block scalar, a line break between
+/// two lines become a space, while the empty lines and the more
+/// indented lines keep the line breaks.
+fn fold_lines(lines: &[String]) -> String {
+    let mut folded = String::new();
+    let mut empty_lines = 0;
+    // None until we find the first content line
+    let mut prev_normal: Option<bool> = None;
+    for line in lines {
+        if line.is_empty() {
+            empty_lines += 1;
+            continue;
+        }
+        let normal = !line.starts_with([' ', '\t']);
+        match prev_normal {
+            None => folded.push_str(&"\n".repeat(empty_lines)),
+            Some(true) if normal && empty_lines == 0 => folded.push(' '),
+            Some(true) if normal => folded.push_str(&"\n".repeat(empty_lines)),
+            Some(_) => folded.push_str(&"\n".repeat(empty_lines + 1)),
+        }
+        folded.push_str(line);
+        prev_normal = Some(normal);
+        empty_lines = 0;
+    }
+    folded
+}
+
+/// Iterate over the chars of the content, where the line breaks
+/// `\r\n` and `\r` are a single `\n`.
+fn line_breaks(content: &str) -> impl Iterator<Item = char> + '_ {
+    let mut chars = content.chars().peekable();
+    std::iter::from_fn(move || match chars.next()? {
+        '\r' => {
+            chars.next_if_eq(&'\n');
+            Some('\n')
+        }
+        char_at => Some(char_at),
+    })
+}
+
+/// Check if the chars start with a document marker, `---` or
+/// `...`, followed by a blank or by the end of the content.
+fn is_document_marker(mut chars: impl Iterator<Item = char>) -> bool {
+    let marker = match chars.next() {
+        Some(marker @ ('-' | '.')) => marker,
+        _ => return false,
+    };
+    chars.next() == Some(marker)
+        && chars.next() == Some(marker)
+        && matches!(chars.next(), None | Some(' ') | Some('\t') | Some('\n'))
+}
+
+/// Check if the char is a flow collection indicator.
+fn is_flow_indicator(char_at: char) -> bool {
+    matches!(char_at, ',' | '[' | ']' | '{' | '}')
+}
+
+/// Resolve a plain scalar with the YAML 1.2 core schema, in a null,
+/// bool, int, float or in a string identifier.
+///
+/// A decimal int out of the range of `i64` is resolved as a float,
+/// and an hexadecimal or octal int out of range is kept as a string.
+pub(crate) fn resolve_plain_scalar(value: &str) -> YamlToken {
+    match value {
+        "~" | "null" | "Null" | "NULL" => YamlToken::NullVal,
+        "true" | "True" | "TRUE" => YamlToken::BoolVal(true),
+        "false" | "False" | "FALSE" => YamlToken::BoolVal(false),
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => {
+            YamlToken::FloatVal(f64::INFINITY)
+        }
+        "-.inf" | "-.Inf" | "-.INF" => YamlToken::FloatVal(f64::NEG_INFINITY),
+        ".nan" | ".NaN" | ".NAN" => YamlToken::FloatVal(f64::NAN),
+        _ => {
+            let radix = [("0x", 16), ("0o", 8)]
+                .into_iter()
+                .find_map(|(prefix, radix)| Some((value.strip_prefix(prefix)?, radix)));
+            if let Some((digits, radix)) = radix {
+                if !digits.is_empty() && digits.chars().all(|digit| digit.is_digit(radix)) {
+                    if let Ok(number) = i64::from_str_radix(digits, radix) {
+                        return YamlToken::IntVal(number);
+                    }
+                }
+            }
+            if is_core_int(value) {
+                if let Ok(number) = value.parse() {
+                    return YamlToken::IntVal(number);
+                }
+            }
+            // an int out of range is still a valid float
+            if is_core_float(value) {
+                if let Ok(number) = value.parse() {
+                    return YamlToken::FloatVal(number);
+                }
+            }
+            YamlToken::Identifier(value.to_string())
+        }
     }
 }
 
+/// Check if the value match `[-+]?[0-9]+`
+fn is_core_int(value: &str) -> bool {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    !digits.is_empty() && digits.chars().all(|digit| digit.is_ascii_digit())
+}
+
+/// Check if the value match
+/// `[-+]?(\.[0-9]+|[0-9]+(\.[0-9]*)?)([eE][-+]?[0-9]+)?`
+fn is_core_float(value: &str) -> bool {
+    let value = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (value, None),
+    };
+    let is_digits = |digits: &str| digits.chars().all(|digit| digit.is_ascii_digit());
+    let valid_mantissa = match mantissa.split_once('.') {
+        Some((int, fract)) => {
+            is_digits(int) && is_digits(fract) && !(int.is_empty() && fract.is_empty())
+        }
+        None => !mantissa.is_empty() && is_digits(mantissa),
+    };
+    let valid_exponent = match exponent {
+        Some(exponent) => is_core_int(exponent),
+        None => true,
+    };
+    valid_mantissa && valid_exponent
+}
+
 /// YamlScanner implementation for the Scanner struct
-impl YamlScanner<YamlToken> for Scanner {
+impl YamlScanner<Token> for Scanner {
     fn new() -> Self {
         Scanner {
             pos: 0,
-            line: 0,
-            tokens: vec![],
-            identifier_symbols: vec!['_'],
+            offset: 0,
+            line: 1,
+            line_start: 0,
+            line_offset: 0,
+            token_start: Span::default(),
+            tokens: VecDeque::new(),
+            flow_level: 0,
+            line_indents: vec![],
+            line_indents_offset: 0,
         }
     }
 
-    fn scan(&mut self, content: &str) -> &Vec<YamlToken> {
-        self.run(&content);
-        &self.tokens
+    fn scan(&mut self, content: &str) -> Result<Vec<Token>, ScannerError> {
+        self.reset(content);
+        self.run(content)?;
+        Ok(std::mem::take(&mut self.tokens).into())
     }
 }
 
 #[cfg(test)]
 mod test {
     use crate::scanner::scanner::{Scanner, YamlScanner, YamlToken};
+    use crate::scanner::ScannerErrorKind;
     use indoc::indoc;
 
     #[test]
@@ -263,9 +1017,37 @@ national:
  - \"Chicago Cubs\"
  - \"Atlanta Braves\"
 "};
-        let tokens = scanner.scan(&simple_yaml);
-        println!("{:?}", tokens);
-        assert!(tokens.len() > 0);
+        let kinds: Vec<YamlToken> = scanner
+            .scan(simple_yaml)
+            .unwrap()
+            .into_iter()
+            .map(|token| token.kind)
+            .filter(|kind| *kind != YamlToken::Space)
+            .collect();
+        let items = |names: [&str; 3]| {
+            names
+                .into_iter()
+                .flat_map(|name| [YamlToken::Dash, YamlToken::StringVal(name.to_string())])
+                .collect::<Vec<_>>()
+        };
+        let mut expected = vec![
+            YamlToken::Pount(" This is a list of document".to_string()),
+            YamlToken::StartDoc,
+            YamlToken::Identifier("american".to_string()),
+            YamlToken::DotDot,
+        ];
+        expected.extend(items([
+            "Boston Red Sox",
+            "Detroit Tigers",
+            "New York Yankees",
+        ]));
+        expected.extend([
+            YamlToken::Identifier("national".to_string()),
+            YamlToken::DotDot,
+        ]);
+        expected.extend(items(["New York Mets", "Chicago Cubs", "Atlanta Braves"]));
+        expected.push(YamlToken::EOF);
+        assert_eq!(kinds, expected);
     }
 
     #[test]
@@ -288,8 +1070,517 @@ ModelOne:
       - nullable: true
       - reference: true"};
 
-        let tokens = scanner.scan(&simple_yaml);
-        print!("{:?}", tokens.last().unwrap());
-        assert_eq!(tokens[tokens.len() - 2], YamlToken::BoolVal(true));
+        let tokens = scanner.scan(simple_yaml).unwrap();
+        assert_eq!(tokens[tokens.len() - 2].kind, YamlToken::BoolVal(true));
+    }
+
+    #[test]
+    fn scan_document_markers() {
+        assert_eq!(
+            scan_kinds("--- # first\nkey: 1\n...\n---\n"),
+            vec![
+                YamlToken::StartDoc,
+                YamlToken::Pount(" first".to_string()),
+                YamlToken::Identifier("key".to_string()),
+                YamlToken::DotDot,
+                YamlToken::IntVal(1),
+                YamlToken::EndDoc,
+                YamlToken::StartDoc,
+                YamlToken::EOF,
+            ]
+        );
+        // the markers are defined only at the beginning of
+        // the line and followed by a blank
+        for content in ["--\n", "..\n", "---x\n", "....\n"] {
+            let value = content.trim_end().to_string();
+            assert_eq!(
+                scan_kinds(content),
+                vec![YamlToken::Identifier(value), YamlToken::EOF]
+            );
+        }
+    }
+
+    #[test]
+    fn scan_dashes_and_dots_in_plain_scalars() {
+        assert_eq!(
+            scan_kinds("path: ../models/a.yml\nkey: ---x\nargs: [--verbose]\n- --verbose\n"),
+            vec![
+                YamlToken::Identifier("path".to_string()),
+                YamlToken::DotDot,
+                YamlToken::Identifier("../models/a.yml".to_string()),
+                YamlToken::Identifier("key".to_string()),
+                YamlToken::DotDot,
+                YamlToken::Identifier("---x".to_string()),
+                YamlToken::Identifier("args".to_string()),
+                YamlToken::DotDot,
+                YamlToken::LeftSquareBrace,
+                YamlToken::Identifier("--verbose".to_string()),
+                YamlToken::RightSquareBrace,
+                YamlToken::Dash,
+                YamlToken::Identifier("--verbose".to_string()),
+                YamlToken::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_unexpected_char() {
+        let mut scanner = Scanner::new();
+        let err = scanner.scan("key:\n  - @value").unwrap_err();
+        assert_eq!(err.kind, ScannerErrorKind::UnexpectedChar('@'));
+        assert_eq!((err.line, err.column, err.offset), (2, 5, 9));
+        assert_eq!(err.snippet, "@");
+    }
+
+    /// Scan the content and return the content of the first
+    /// multiline token found.
+    fn scan_block(content: &str) -> String {
+        let tokens = Scanner::new().scan(content).unwrap();
+        tokens
+            .into_iter()
+            .find_map(|token| match token.kind {
+                YamlToken::Multiline(value) => Some(value),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn scan_literal_block_scalar() {
+        let content = indoc! {"
+doc: |
+  first line
+    indented line
+
+  last line
+next: 1
+"};
+        assert_eq!(
+            scan_block(content),
+            "first line\n  indented line\n\nlast line\n"
+        );
+        let tokens = Scanner::new().scan(content).unwrap();
+        assert!(tokens
+            .iter()
+            .any(|token| token.kind == YamlToken::Identifier("next".to_string())));
+    }
+
+    #[test]
+    fn scan_folded_block_scalar() {
+        let content = "doc: >\n  folded\n  line\n\n  next paragraph\n    more indented\n  end\n";
+        assert_eq!(
+            scan_block(content),
+            "folded line\nnext paragraph\n  more indented\nend\n"
+        );
+    }
+
+    #[test]
+    fn scan_block_scalar_chomping() {
+        assert_eq!(scan_block("doc: |-\n  text\n\n"), "text");
+        assert_eq!(scan_block("doc: |\n  text\n\n"), "text\n");
+        assert_eq!(scan_block("doc: |+\n  text\n\n"), "text\n\n");
+        assert_eq!(scan_block("doc: >-\n  one\n  two\n"), "one two");
+    }
+
+    #[test]
+    fn scan_block_scalar_indentation_indicator() {
+        assert_eq!(scan_block("doc: |2\n    text\n  end\n"), "  text\nend\n");
+        assert_eq!(
+            scan_block("- doc: |1-\n    text # not a comment\n"),
+            " text # not a comment"
+        );
+        // the indicator is relative to the sequence, and not to the `-`
+        assert_eq!(scan_block("- |2\n   x\n"), " x\n");
+        assert_eq!(scan_block("- |\n  item\n- next\n"), "item\n");
+        let err = Scanner::new().scan("doc: |x\n  text\n").unwrap_err();
+        assert_eq!(err.kind, ScannerErrorKind::MalformedBlockScalar);
+    }
+
+    #[test]
+    fn scan_root_block_scalar() {
+        assert_eq!(scan_block("--- |\n  foo\n  bar\n"), "foo\nbar\n");
+        assert_eq!(scan_block("--- >\nfolded\nline\n---\n"), "folded line\n");
+        assert_eq!(
+            scan_kinds("--- |\nroot\n...\n")[2..],
+            [YamlToken::EndDoc, YamlToken::EOF]
+        );
+    }
+
+    #[test]
+    fn scan_flow_sequence() {
+        let tokens = Scanner::new().scan("[a, 1]").unwrap();
+        let kinds: Vec<YamlToken> = tokens.into_iter().map(|token| token.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                YamlToken::LeftSquareBrace,
+                YamlToken::Identifier("a".to_string()),
+                YamlToken::Comma,
+                YamlToken::Space,
+                YamlToken::IntVal(1),
+                YamlToken::RightSquareBrace,
+                YamlToken::EOF,
+            ]
+        );
+    }
+
+    /// Scan the content and return the kind of the tokens found
+    /// without the spaces.
+    fn scan_kinds(content: &str) -> Vec<YamlToken> {
+        let tokens = Scanner::new().scan(content).unwrap();
+        tokens
+            .into_iter()
+            .map(|token| token.kind)
+            .filter(|kind| *kind != YamlToken::Space)
+            .collect()
+    }
+
+    #[test]
+    fn scan_directives() {
+        assert_eq!(
+            scan_kinds("%YAML 1.2 # version\n---\nkey: 100%\n...\n"),
+            vec![
+                YamlToken::Directive("YAML 1.2".to_string()),
+                YamlToken::Pount(" version".to_string()),
+                YamlToken::StartDoc,
+                YamlToken::Identifier("key".to_string()),
+                YamlToken::DotDot,
+                YamlToken::Identifier("100%".to_string()),
+                YamlToken::EndDoc,
+                YamlToken::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_anchors_and_aliases() {
+        assert_eq!(
+            scan_kinds("base: &base {a: *other}\n"),
+            vec![
+                YamlToken::Identifier("base".to_string()),
+                YamlToken::DotDot,
+                YamlToken::Anchor("base".to_string()),
+                YamlToken::LeftCurlyBrace,
+                YamlToken::Identifier("a".to_string()),
+                YamlToken::DotDot,
+                YamlToken::Alias("other".to_string()),
+                YamlToken::RightCurlyBrace,
+                YamlToken::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_tags() {
+        assert_eq!(
+            scan_kinds("a: !!int 12\nb: [!<tag:x.com> 1, !ref Model]\nc: !!str &c true\n"),
+            vec![
+                YamlToken::Identifier("a".to_string()),
+                YamlToken::DotDot,
+                YamlToken::Tag("!!int".to_string()),
+                YamlToken::Identifier("12".to_string()),
+                YamlToken::Identifier("b".to_string()),
+                YamlToken::DotDot,
+                YamlToken::LeftSquareBrace,
+                YamlToken::Tag("!<tag:x.com>".to_string()),
+                YamlToken::Identifier("1".to_string()),
+                YamlToken::Comma,
+                YamlToken::Tag("!ref".to_string()),
+                YamlToken::Identifier("Model".to_string()),
+                YamlToken::RightSquareBrace,
+                YamlToken::Identifier("c".to_string()),
+                YamlToken::DotDot,
+                YamlToken::Tag("!!str".to_string()),
+                YamlToken::Anchor("c".to_string()),
+                YamlToken::Identifier("true".to_string()),
+                YamlToken::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_tab_indentation() {
+        assert_eq!(
+            scan_kinds("key:\tvalue\n"),
+            vec![
+                YamlToken::Identifier("key".to_string()),
+                YamlToken::DotDot,
+                YamlToken::TabSpace,
+                YamlToken::Identifier("value".to_string()),
+                YamlToken::EOF,
+            ]
+        );
+        let err = Scanner::new().scan("key:\n  \t- value\n").unwrap_err();
+        assert_eq!(err.kind, ScannerErrorKind::TabIndentation);
+        assert_eq!((err.line, err.column), (2, 3));
+    }
+
+    #[test]
+    fn scan_plain_scalars() {
+        assert_eq!(
+            scan_kinds("- Boston Red Sox  # team\ntype: map[string]int\n"),
+            vec![
+                YamlToken::Dash,
+                YamlToken::Identifier("Boston Red Sox".to_string()),
+                YamlToken::Pount(" team".to_string()),
+                YamlToken::Identifier("type".to_string()),
+                YamlToken::DotDot,
+                YamlToken::Identifier("map[string]int".to_string()),
+                YamlToken::EOF,
+            ]
+        );
+        assert_eq!(
+            scan_kinds("url: http://example.com/a#b"),
+            vec![
+                YamlToken::Identifier("url".to_string()),
+                YamlToken::DotDot,
+                YamlToken::Identifier("http://example.com/a#b".to_string()),
+                YamlToken::EOF,
+            ]
+        );
+        assert_eq!(
+            scan_kinds("[a b, c]"),
+            vec![
+                YamlToken::LeftSquareBrace,
+                YamlToken::Identifier("a b".to_string()),
+                YamlToken::Comma,
+                YamlToken::Identifier("c".to_string()),
+                YamlToken::RightSquareBrace,
+                YamlToken::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_multiline_plain_scalar() {
+        assert_eq!(
+            scan_kinds("doc: first line\n  second line\n\n  third\nnext: 1"),
+            vec![
+                YamlToken::Identifier("doc".to_string()),
+                YamlToken::DotDot,
+                YamlToken::Identifier("first line second line\nthird".to_string()),
+                YamlToken::Identifier("next".to_string()),
+                YamlToken::DotDot,
+                YamlToken::IntVal(1),
+                YamlToken::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_multiline_plain_scalar_parent_indent() {
+        let kinds = scan_kinds("plain:\n  This unquoted\n  spans lines.\nnext: 1\n");
+        assert_eq!(
+            kinds[2],
+            YamlToken::Identifier("This unquoted spans lines.".to_string())
+        );
+        assert_eq!(kinds[3], YamlToken::Identifier("next".to_string()));
+        assert_eq!(
+            scan_kinds("- first\n  item\n-  second\n   item\n")[1],
+            YamlToken::Identifier("first item".to_string())
+        );
+        assert_eq!(
+            scan_kinds("- key: first\n    line\n")[3],
+            YamlToken::Identifier("first line".to_string())
+        );
+        assert_eq!(
+            scan_kinds("---\nroot\nscalar\n---\nnext\n"),
+            vec![
+                YamlToken::StartDoc,
+                YamlToken::Identifier("root scalar".to_string()),
+                YamlToken::StartDoc,
+                YamlToken::Identifier("next".to_string()),
+                YamlToken::EOF,
+            ]
+        );
+        // the parent of each scalar is found from the lines before it
+        let scalars: Vec<YamlToken> = scan_kinds("a:\n  b:\n    c\n     d\n  e:\n   f\n    g\n")
+            .into_iter()
+            .filter(|kind| matches!(kind, YamlToken::Identifier(_)))
+            .collect();
+        assert_eq!(
+            scalars,
+            ["a", "b", "c d", "e", "f g"].map(|scalar| YamlToken::Identifier(scalar.to_string()))
+        );
+        // the scalars inside a flow collection do not have a parent
+        assert_eq!(
+            scan_kinds("[\na\nb,\n]\n")[1],
+            YamlToken::Identifier("a b".to_string())
+        );
+    }
+
+    #[test]
+    fn scan_core_schema_scalars() {
+        let resolve = |content: &str| scan_kinds(content).remove(0);
+        assert_eq!(resolve("~"), YamlToken::NullVal);
+        assert_eq!(resolve("null"), YamlToken::NullVal);
+        assert_eq!(resolve("NULL"), YamlToken::NullVal);
+        assert_eq!(resolve("True"), YamlToken::BoolVal(true));
+        assert_eq!(resolve("FALSE"), YamlToken::BoolVal(false));
+        assert_eq!(resolve("-12"), YamlToken::IntVal(-12));
+        assert_eq!(resolve("+12"), YamlToken::IntVal(12));
+        assert_eq!(resolve("0x1F"), YamlToken::IntVal(31));
+        assert_eq!(resolve("0o17"), YamlToken::IntVal(15));
+        assert_eq!(resolve("1e10"), YamlToken::FloatVal(1e10));
+        assert_eq!(resolve("-.5"), YamlToken::FloatVal(-0.5));
+        assert_eq!(resolve("2.5E-3"), YamlToken::FloatVal(2.5e-3));
+        assert_eq!(resolve(".inf"), YamlToken::FloatVal(f64::INFINITY));
+        assert_eq!(resolve("-.Inf"), YamlToken::FloatVal(f64::NEG_INFINITY));
+        assert!(matches!(resolve(".NaN"), YamlToken::FloatVal(val) if val.is_nan()));
+        // not part of the core schema, so they are strings
+        assert_eq!(resolve("yes"), YamlToken::Identifier("yes".to_string()));
+        assert_eq!(resolve("0x"), YamlToken::Identifier("0x".to_string()));
+        assert_eq!(resolve("1.2.3"), YamlToken::Identifier("1.2.3".to_string()));
+        assert_eq!(resolve("12:30"), YamlToken::Identifier("12:30".to_string()));
+
+        // the ints out of the range of `i64`
+        assert_eq!(
+            resolve("99999999999999999999"),
+            YamlToken::FloatVal(99999999999999999999.0)
+        );
+        assert_eq!(
+            resolve("-99999999999999999999"),
+            YamlToken::FloatVal(-99999999999999999999.0)
+        );
+        assert_eq!(
+            resolve("0xFFFFFFFFFFFFFFFFFF"),
+            YamlToken::Identifier("0xFFFFFFFFFFFFFFFFFF".to_string())
+        );
+    }
+
+    #[test]
+    fn scan_quoted_strings() {
+        let string = |content: &str| match scan_kinds(content).remove(0) {
+            YamlToken::StringVal(value) => value,
+            token => panic!("expected a string, found {:?}", token),
+        };
+        assert_eq!(string(r#""say \"hi\"""#), "say \"hi\"");
+        assert_eq!(string(r#""tab\tnew\nline\\""#), "tab\tnew\nline\\");
+        assert_eq!(string(r#""è \x41 \U0001F600""#), "è A 😀");
+        assert_eq!(string("'it''s \"quoted\"'"), "it's \"quoted\"");
+        assert_eq!(string(r#"'no \n escape'"#), "no \\n escape");
+        assert_eq!(
+            string("\"folded  \n   line\n\n  next\""),
+            "folded line\nnext"
+        );
+        assert_eq!(string("'single\n  folded'"), "single folded");
+        assert_eq!(string("\"joined \\\n   line\""), "joined line");
+    }
+
+    #[test]
+    fn scan_invalid_quoted_strings() {
+        let err = Scanner::new()
+            .scan("doc: \"not closed\nkey: 1")
+            .unwrap_err();
+        assert_eq!(err.kind, ScannerErrorKind::UnterminatedString);
+        assert_eq!((err.line, err.column), (1, 6));
+
+        let err = Scanner::new().scan("doc: 'not closed").unwrap_err();
+        assert_eq!(err.kind, ScannerErrorKind::UnterminatedString);
+
+        let err = Scanner::new().scan(r#"doc: "bad \q escape""#).unwrap_err();
+        assert_eq!(err.kind, ScannerErrorKind::InvalidEscape('q'));
+        assert_eq!((err.line, err.column), (1, 11));
+        assert_eq!(err.snippet, "\\q");
+
+        let err = Scanner::new().scan(r#"doc: "\u00""#).unwrap_err();
+        assert_eq!(err.kind, ScannerErrorKind::InvalidEscape('u'));
+    }
+
+    #[test]
+    fn scan_non_ascii() {
+        let content = "città: \"perché\" # è\nnome: Zoë\n";
+        let tokens = Scanner::new().scan(content).unwrap();
+        let value = &tokens[3];
+        assert_eq!(value.kind, YamlToken::StringVal("perché".to_string()));
+        assert_eq!(&content[value.span.start..value.span.end], "\"perché\"");
+        assert_eq!((value.span.line, value.span.column), (1, 8));
+        let name = tokens
+            .iter()
+            .find(|token| token.kind == YamlToken::Identifier("Zoë".to_string()))
+            .unwrap();
+        assert_eq!((name.span.line, name.span.column), (2, 7));
+
+        let err = Scanner::new().scan("città: \"perché").unwrap_err();
+        assert_eq!(err.kind, ScannerErrorKind::UnterminatedString);
+        assert_eq!(err.column, 8);
+        assert_eq!(err.snippet, "\"perché");
+    }
+
+    #[test]
+    fn scan_line_breaks() {
+        let kinds = vec![
+            YamlToken::Identifier("a".to_string()),
+            YamlToken::DotDot,
+            YamlToken::IntVal(1),
+            YamlToken::Identifier("b".to_string()),
+            YamlToken::DotDot,
+            YamlToken::IntVal(2),
+            YamlToken::EOF,
+        ];
+        assert_eq!(scan_kinds("a: 1\r\nb: 2\r\n"), kinds);
+        assert_eq!(scan_kinds("a: 1\rb: 2\r"), kinds);
+        assert_eq!(scan_block("doc: |\r\n  one\r\n  two\r\n"), "one\ntwo\n");
+        assert_eq!(
+            scan_kinds("doc: \"one\r\n  two\" # end\r\n")[2],
+            YamlToken::StringVal("one two".to_string())
+        );
+        let tokens = Scanner::new().scan("a: 1\r\nb: 2").unwrap();
+        let key = &tokens[4];
+        assert_eq!(key.kind, YamlToken::Identifier("b".to_string()));
+        assert_eq!((key.span.start, key.span.line, key.span.column), (6, 2, 1));
+    }
+
+    #[test]
+    fn scan_byte_order_mark() {
+        let tokens = Scanner::new()
+            .scan("\u{feff}%YAML 1.2\n---\nkey: 1")
+            .unwrap();
+        assert_eq!(tokens[0].kind, YamlToken::Directive("YAML 1.2".to_string()));
+        assert_eq!((tokens[0].span.start, tokens[0].span.column), (3, 1));
+        assert_eq!(
+            scan_kinds("\u{feff}key: 1")[0],
+            YamlToken::Identifier("key".to_string())
+        );
+    }
+
+    #[test]
+    fn scan_tokens_iterator() {
+        let content = indoc! {"
+        # models
+        ModelOne:
+          - PropOne: {type: int64}
+          - doc: |
+              multi
+              line
+        "};
+        let tokens = Scanner::new().scan(content).unwrap();
+        let streamed = Scanner::new()
+            .tokens(content)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(tokens, streamed);
+
+        let mut tokens = Scanner::new().tokens("a:\n\tb: 2\n");
+        assert!(tokens.by_ref().take(2).all(|token| token.is_ok()));
+        let err = tokens.next().unwrap().unwrap_err();
+        assert_eq!(err.kind, ScannerErrorKind::TabIndentation);
+        assert!(tokens.next().is_none());
+    }
+
+    #[test]
+    fn scan_tokens_keep_only_the_current_line() {
+        let content = "- {name: a, type: int64}\n".repeat(10_000);
+        let mut tokens = Scanner::new().tokens(&content);
+        let mut count = 0;
+        let mut max_buffered = 0;
+        while let Some(token) = tokens.next() {
+            token.unwrap();
+            count += 1;
+            max_buffered = max_buffered.max(tokens.scanner.tokens.len());
+        }
+        assert_eq!(count, Scanner::new().scan(&content).unwrap().len());
+        // the tokens of a single line, and not of the whole content
+        assert!(max_buffered <= 16, "{} tokens buffered", max_buffered);
     }
 }
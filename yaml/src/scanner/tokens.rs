@@ -1,6 +1,9 @@
 //! Yaml Tokens definition
 //!
 //! author: https://github.com/vincenzopalazzo
+use std::fmt;
+
+use crate::span::Span;
 
 /// Enumerator to define the Yaml language
 #[derive(Debug, PartialEq, Clone)]
@@ -25,8 +28,18 @@ pub enum YamlToken {
     DotDot,
     /// #, Comment line token
     Pount(String),
+    /// %, Directive line token, e.g: `%YAML 1.2`
+    Directive(String),
+    /// &, Anchor of the node that follow, e.g: `&audit`
+    Anchor(String),
+    /// *, Alias to a node with an anchor, e.g: `*audit`
+    Alias(String),
+    /// !, Tag of the node that follow, e.g: `!!str` or `!ref`
+    Tag(String),
     /// -, Dash token to listing items
     Dash,
+    /// ,, Comma token to divide the items of a flow collection
+    Comma,
     LeftCurlyBrace,
     RightCurlyBrace,
     LeftSquareBrace,
@@ -36,3 +49,48 @@ pub enum YamlToken {
     /// EOF put the end of the tokens
     EOF,
 }
+
+impl fmt::Display for YamlToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            YamlToken::Identifier(name) => write!(f, "`{}`", name),
+            YamlToken::NullVal => write!(f, "`null`"),
+            YamlToken::IntVal(val) => write!(f, "`{}`", val),
+            YamlToken::FloatVal(val) => write!(f, "`{}`", val),
+            YamlToken::StringVal(val) => write!(f, "`{:?}`", val),
+            YamlToken::BoolVal(val) => write!(f, "`{}`", val),
+            YamlToken::StartDoc => write!(f, "`---`"),
+            YamlToken::EndDoc => write!(f, "`...`"),
+            YamlToken::Space => write!(f, "space"),
+            YamlToken::TabSpace => write!(f, "tab"),
+            YamlToken::DotDot => write!(f, "`:`"),
+            YamlToken::Pount(_) => write!(f, "comment"),
+            YamlToken::Directive(directive) => write!(f, "`%{}`", directive),
+            YamlToken::Anchor(name) => write!(f, "`&{}`", name),
+            YamlToken::Alias(name) => write!(f, "`*{}`", name),
+            YamlToken::Tag(tag) => write!(f, "`{}`", tag),
+            YamlToken::Dash => write!(f, "`-`"),
+            YamlToken::Comma => write!(f, "`,`"),
+            YamlToken::LeftCurlyBrace => write!(f, "`{{`"),
+            YamlToken::RightCurlyBrace => write!(f, "`}}`"),
+            YamlToken::LeftSquareBrace => write!(f, "`[`"),
+            YamlToken::RightSquareBrace => write!(f, "`]`"),
+            YamlToken::Multiline(_) => write!(f, "multiline content"),
+            YamlToken::EOF => write!(f, "end of file"),
+        }
+    }
+}
+
+/// Token found by the scanner, with the position
+/// in the content where it is defined.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Token {
+    pub kind: YamlToken,
+    pub span: Span,
+}
+
+impl Token {
+    pub fn new(kind: YamlToken, span: Span) -> Self {
+        Token { kind, span }
+    }
+}
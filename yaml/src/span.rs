@@ -0,0 +1,44 @@
+//! Span implementation to track the position of
+//! a token or a node inside the YAML content.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::fmt;
+
+/// Position of a piece of content inside the YAML source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span {
+    /// byte offset where the content start
+    pub start: usize,
+    /// byte offset where the content end (excluded)
+    pub end: usize,
+    /// line where the content start, starting from 1
+    pub line: usize,
+    /// column where the content start, starting from 1
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /// Build a span that start at the beginning of `self`
+    /// and end at the end of `other`.
+    pub fn merge(&self, other: &Span) -> Span {
+        Span {
+            end: other.end.max(self.end),
+            ..*self
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
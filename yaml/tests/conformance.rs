@@ -0,0 +1,263 @@
+//! Conformance harness over the YAML test suite
+//! https://github.com/yaml/yaml-test-suite
+//!
+//! The harness reads the `data` layout of the suite, where each
+//! case is a directory with `in.yaml`, the expected `in.json` and an
+//! empty `error` file when the input must be rejected, while the
+//! feature areas are the directories inside `tags`.
+//!
+//! By default it runs the cases vendored in `tests/yaml-test-suite`,
+//! the full suite can be used by setting `YAML_TEST_SUITE` to a
+//! checkout of the `data` branch.
+//!
+//! Each case must pass, unless it is in `tests/conformance.skip`
+//! with the reason why it is skipped.
+//!
+//! author: https://github.com/vincenzopalazzo
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use monkey_yaml::parser::parser::Parser;
+use monkey_yaml::parser::tokens::{YamlNode, YamlObject};
+use monkey_yaml::parser::YamlParser;
+use monkey_yaml::scanner::scanner::Scanner;
+use monkey_yaml::scanner::YamlScanner;
+use serde_json::{Map, Number, Value};
+
+/// Single case of the suite.
+struct Case {
+    id: String,
+    path: PathBuf,
+}
+
+/// Outcome of a case, with the reason of the failure.
+type Outcome = Result<(), String>;
+
+fn suite_dir() -> PathBuf {
+    match std::env::var_os("YAML_TEST_SUITE") {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/yaml-test-suite"),
+    }
+}
+
+/// Collect the cases, the suite store the cases with more
+/// than one input as `<ID>/00`, `<ID>/01` and so on.
+fn collect(dir: &Path, prefix: &str, cases: &mut Vec<Case>) {
+    let mut entries = fs::read_dir(dir)
+        .unwrap_or_else(|err| panic!("unable to read {}: {err}", dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect::<Vec<_>>();
+    entries.sort();
+    for path in entries {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        if prefix.is_empty() && (name == "tags" || name == "name" || name.starts_with('.')) {
+            continue;
+        }
+        let id = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}/{name}")
+        };
+        if path.join("in.yaml").exists() {
+            cases.push(Case { id, path });
+        } else {
+            collect(&path, &id, cases);
+        }
+    }
+}
+
+/// Map each case id to the feature areas that it belongs.
+fn areas(dir: &Path) -> BTreeMap<String, Vec<String>> {
+    let mut areas: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let Ok(tags) = fs::read_dir(dir.join("tags")) else {
+        return areas;
+    };
+    for tag in tags {
+        let tag = tag.unwrap().path();
+        let area = tag.file_name().unwrap().to_string_lossy().to_string();
+        for case in fs::read_dir(&tag).unwrap() {
+            let id = case.unwrap().file_name().to_string_lossy().to_string();
+            areas.entry(id).or_default().push(area.clone());
+        }
+    }
+    areas
+}
+
+fn load(content: &str) -> Result<Vec<YamlNode>, String> {
+    let tokens = Scanner::new()
+        .scan(content)
+        .map_err(|err| format!("scanner: {err}"))?;
+    Parser::new()
+        .parse(&tokens)
+        .map_err(|err| format!("parser: {}", err.message()))
+}
+
+/// Lower the YAML node to the JSON value used by `in.json`.
+fn to_json(node: &YamlNode) -> Result<Value, String> {
+    let value = match &node.object {
+        YamlObject::Document(nodes) => match nodes
+            .iter()
+            .find(|node| !matches!(node.object, YamlObject::Comment(_)))
+        {
+            Some(root) => to_json(root)?,
+            None => Value::Null,
+        },
+        YamlObject::Reference(_, node) => to_json(node)?,
+        YamlObject::Tagged(_, node) => to_json(node)?,
+        YamlObject::Mapping(entries, _) => {
+            let mut map = Map::new();
+            for entry in entries {
+                let key = match to_json(&entry.key)? {
+                    Value::String(key) => key,
+                    Value::Null => "".to_owned(),
+                    Value::Array(_) | Value::Object(_) => {
+                        return Err("collection used as key".to_owned())
+                    }
+                    key => key.to_string(),
+                };
+                map.insert(key, to_json(&entry.value)?);
+            }
+            Value::Object(map)
+        }
+        YamlObject::List(items) => {
+            Value::Array(items.iter().map(to_json).collect::<Result<_, _>>()?)
+        }
+        YamlObject::Str(value) => Value::String(value.to_owned()),
+        YamlObject::Int(value) => Value::Number(Number::from(*value)),
+        YamlObject::Float(value) => Number::from_f64(*value)
+            .map(Value::Number)
+            .ok_or_else(|| format!("float {value} without JSON representation"))?,
+        YamlObject::Bool(value) => Value::Bool(*value),
+        YamlObject::Null => Value::Null,
+        YamlObject::Comment(_) => return Err(format!("unexpected node {:?}", node.object)),
+    };
+    Ok(value)
+}
+
+/// Compare two JSON values, the numbers are equal when
+/// they have the same value, whatever their representation.
+fn same(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64() == right.as_f64(),
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len() && left.iter().zip(right).all(|(l, r)| same(l, r))
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .all(|(key, value)| right.get(key).is_some_and(|other| same(value, other)))
+        }
+        _ => left == right,
+    }
+}
+
+fn run(case: &Case) -> Outcome {
+    let content = fs::read_to_string(case.path.join("in.yaml")).map_err(|err| err.to_string())?;
+    let documents = load(&content);
+    if case.path.join("error").exists() {
+        return match documents {
+            Ok(_) => Err("invalid input accepted".to_owned()),
+            Err(_) => Ok(()),
+        };
+    }
+    let documents = documents?;
+    let Ok(json) = fs::read_to_string(case.path.join("in.json")) else {
+        // without a JSON representation, e.g: complex keys,
+        // it is enough that the input is accepted.
+        return Ok(());
+    };
+    let expected = serde_json::Deserializer::from_str(&json)
+        .into_iter::<Value>()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("invalid in.json: {err}"))?;
+    let actual = documents
+        .iter()
+        .map(to_json)
+        .collect::<Result<Vec<_>, _>>()?;
+    if expected.len() != actual.len() {
+        return Err(format!(
+            "expected {} documents, found {}",
+            expected.len(),
+            actual.len()
+        ));
+    }
+    for (expected, actual) in expected.iter().zip(&actual) {
+        if !same(expected, actual) {
+            return Err(format!("expected `{expected}`, found `{actual}`"));
+        }
+    }
+    Ok(())
+}
+
+/// The cases that are known to fail, one id for line followed
+/// by the reason, e.g: `M5DY # complex keys are not supported`.
+fn skip_list() -> BTreeSet<String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/conformance.skip");
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.split_once('#') {
+            Some((id, reason)) if !reason.trim().is_empty() => id.trim().to_owned(),
+            _ => panic!("skipped case without a reason: `{line}`"),
+        })
+        .collect()
+}
+
+#[test]
+fn yaml_test_suite() {
+    let dir = suite_dir();
+    let mut cases = vec![];
+    collect(&dir, "", &mut cases);
+    let areas = areas(&dir);
+    let skip_list = skip_list();
+
+    let mut report: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    let mut failures = vec![];
+    let mut fixed = vec![];
+    let mut passed = 0;
+    for case in &cases {
+        let outcome = run(case);
+        let root = case.id.split('/').next().unwrap();
+        let untagged = vec!["untagged".to_owned()];
+        for area in areas.get(root).unwrap_or(&untagged) {
+            let (pass, fail) = report.entry(area.to_owned()).or_default();
+            match outcome {
+                Ok(_) => *pass += 1,
+                Err(_) => *fail += 1,
+            }
+        }
+        let skipped = skip_list.contains(&case.id);
+        match outcome {
+            Ok(_) if skipped => fixed.push(case.id.clone()),
+            Ok(_) => passed += 1,
+            Err(err) if skipped => println!("SKIP {}: {err}", case.id),
+            Err(err) => {
+                println!("FAIL {}: {err}", case.id);
+                failures.push(format!("{}: {err}", case.id));
+            }
+        }
+    }
+
+    println!("{:<20} {:>6} {:>6}", "area", "pass", "fail");
+    for (area, (pass, fail)) in &report {
+        println!("{area:<20} {pass:>6} {fail:>6}");
+    }
+    println!("{:<20} {:>6} {:>6}", "total", passed, cases.len() - passed);
+
+    assert!(!cases.is_empty(), "no cases found in {}", dir.display());
+    assert!(
+        failures.is_empty(),
+        "cases that fail and are not skipped:\n{}",
+        failures.join("\n")
+    );
+    assert!(
+        fixed.is_empty(),
+        "skipped cases that pass, remove them from the skip list:\n{}",
+        fixed.join("\n")
+    );
+}